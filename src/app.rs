@@ -1,4 +1,7 @@
-use std::{io::Stdout, time::Duration};
+use std::{
+    io::Stdout,
+    time::{Duration, Instant},
+};
 use strum::{Display, EnumIter, FromRepr};
 
 use color_eyre::{eyre::Ok, Result};
@@ -7,36 +10,120 @@ use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
     layout::{Alignment, Rect},
     prelude::CrosstermBackend,
-    widgets::{Block, ScrollbarState},
+    style::{Color, Style},
+    widgets::{Block, Clear, ScrollbarState},
     Frame, Terminal,
 };
-use sysinfo::{System, SystemExt};
+use sysinfo::{Pid, ProcessExt, System, SystemExt};
 use tokio::{sync::mpsc::Sender, time::interval};
 
-use crate::memory::create_memory_gauges;
+use crate::columns::{create_column_picker, ColumnPicker, ColumnPickerAction};
+use crate::config::Config;
+use crate::connections::{create_connections_table, ConnectionsView, ConnectionsViewAction};
+use crate::details::{create_details_widget, DetailsView, DetailsViewAction};
+use crate::export::export_processes_to_file;
+use crate::files::{create_open_files_table, OpenFilesView, OpenFilesViewAction};
+use crate::filter::{FilterMode, ProcessFilter};
+use crate::memory::{
+    create_memory_breakdown, create_memory_gauge, create_memory_history_chart, MemInfo,
+    MemoryHistory,
+};
 use crate::network::create_networks_widget;
-use crate::processes::create_processes_table;
+use crate::pressure::{create_pressure_widget, list_pressure_cgroups, PressureHistory};
+use crate::processes::{
+    build_process_view, create_processes_table, ProcessMarks, ProcessPins, ProcessView, UserNames,
+};
+use crate::procstat::ProcStatCache;
+use crate::scheduling::{
+    create_scheduling_dialog, scheduling_dialog_height, SchedulingDialog, SchedulingDialogAction,
+};
+use crate::signals::{create_signal_dialog, send_signal, SignalDialog, SignalDialogAction};
+use crate::swap::{create_swap_widget, SwapInfo};
+use crate::threads::{create_threads_table, ThreadsView, ThreadsViewAction};
+use crate::vmstat::VmStatSampler;
 use crate::{
+    cgroup::CgroupMonitor,
     cpu::create_cpu_barchart,
-    layout::{is_within_rect, prepare_layout, AppLayout},
+    layout::{centered_rect, is_within_rect, prepare_layout, AppLayout},
 };
 use crate::{disk::create_disks_widget, layout::get_vertical_scrollbar};
 use crate::{
-    layout::{get_horizontal_scrollbar, MemoryLayout},
-    processes::{ProcessColumn, SortDirection},
+    layout::{get_horizontal_scrollbar, MemoryLayout, PressureLayout, ZoomedPanel},
+    processes::{
+        group_key, ProcessColumn, ProcessGroupState, ProcessGrouping, ProcessSelection,
+        ProcessTreeState, SortDirection, SortKeys,
+    },
 };
 
 pub struct App {
     state: AppState,
     layout_clone: AppLayout,
     selected_tab: SelectedTab,
+    zoomed: bool,
     cpu_scrollbar_state: HorizontalScrollbarState,
+    memory_scrollbar_state: VerticalScrollbarState,
     processes_scrollbar_state: VerticalScrollbarState,
-    process_sort_state: Option<(ProcessColumn, SortDirection)>,
+    process_sort_state: SortKeys,
+    process_columns: Vec<ProcessColumn>,
+    // iotop-style mode, shows the IO columns ranked by throughput
+    io_mode: bool,
+    // Sort keys to restore when leaving IO mode
+    sort_before_io_mode: SortKeys,
+    proc_stats: ProcStatCache,
+    process_tree_state: ProcessTreeState,
+    process_group_state: ProcessGroupState,
+    process_pins: ProcessPins,
+    process_marks: ProcessMarks,
+    process_selection: ProcessSelection,
+    // Rebuilt after data refreshes and settings changes, not on every draw
+    process_view: ProcessView,
+    process_view_stale: bool,
+    user_names: UserNames,
+    // PIDs of the rows on screen at the last draw
+    visible_pids: Vec<Pid>,
+    process_filter: ProcessFilter,
+    // Named queries from the config, in name order
+    views: Vec<(String, String)>,
+    dialog: Option<Dialog>,
+    status_message: Option<StatusMessage>,
     disks_scrollbar_state: VerticalScrollbarState,
     networks_scrollbar_state: VerticalScrollbarState,
+    meminfo: Option<MemInfo>,
+    memory_history: MemoryHistory,
+    swap_info: SwapInfo,
+    vmstat: VmStatSampler,
+    pressure_history: PressureHistory,
+    // cgroup (relative to the cgroup2 mount) whose pressure is shown instead of the system one
+    pressure_cgroup: Option<String>,
+    cgroup_monitor: CgroupMonitor,
+    // Shows memory and CPU usage against the limits of reson's own cgroup instead of the host
+    cgroup_mode: bool,
+    // Kept to write back settings changed from the UI
+    config: Config,
 }
 
+/// Modal window drawn over the panels, it gets all key presses while open.
+enum Dialog {
+    Signal(SignalDialog),
+    Scheduling(SchedulingDialog),
+    Details(Box<DetailsView>),
+    Threads(ThreadsView),
+    OpenFiles(OpenFilesView),
+    Connections(ConnectionsView),
+    Columns(ColumnPicker),
+}
+
+struct StatusMessage {
+    text: String,
+    is_error: bool,
+    created: Instant,
+}
+
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
+// What Shift+1 to Shift+9 type on a US layout, for terminals that only report the typed symbol
+const SHIFTED_DIGITS: &str = "!@#$%^&*(";
+
 #[derive(Default, Clone, Copy, PartialEq, Eq)]
 enum AppState {
     #[default]
@@ -49,6 +136,9 @@ enum SelectedTab {
     #[strum(to_string = "CPU")]
     Cpu,
 
+    #[strum(to_string = "Memory")]
+    Memory,
+
     #[strum(to_string = "Processes")]
     Processes,
 
@@ -66,7 +156,8 @@ enum SelectedTab {
 impl SelectedTab {
     fn next(&self) -> Self {
         match self {
-            Self::Cpu => Self::Processes,
+            Self::Cpu => Self::Memory,
+            Self::Memory => Self::Processes,
             Self::Processes => Self::Disks,
             Self::Disks => Self::Networks,
             Self::Networks => Self::None,
@@ -78,7 +169,8 @@ impl SelectedTab {
         match self {
             Self::None => Self::Networks,
             Self::Cpu => Self::None,
-            Self::Processes => Self::Cpu,
+            Self::Memory => Self::Cpu,
+            Self::Processes => Self::Memory,
             Self::Disks => Self::Processes,
             Self::Networks => Self::Disks,
         }
@@ -88,6 +180,21 @@ impl SelectedTab {
         matches!(self, SelectedTab::Cpu)
     }
 
+    fn zoomed_panel(&self) -> Option<ZoomedPanel> {
+        match self {
+            Self::Cpu => Some(ZoomedPanel::Cpu),
+            Self::Memory => Some(ZoomedPanel::Memory),
+            Self::Processes => Some(ZoomedPanel::Processes),
+            Self::Disks => Some(ZoomedPanel::Disks),
+            Self::Networks => Some(ZoomedPanel::Networks),
+            Self::None => None,
+        }
+    }
+
+    fn is_memory(&self) -> bool {
+        matches!(self, SelectedTab::Memory)
+    }
+
     fn is_processes(&self) -> bool {
         matches!(self, SelectedTab::Processes)
     }
@@ -187,6 +294,8 @@ impl VerticalScrollbarState {
 #[derive(Debug)]
 enum InputMessage {
     KeyPress(KeyCode),
+    // Shift+1 to Shift+9, from terminals that report the digit with the modifier
+    ShiftDigit(char),
     MouseScroll { direction: MouseScrollDirection },
     MouseMoved { position: (u16, u16) },
     Quit,
@@ -201,23 +310,46 @@ enum MouseScrollDirection {
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         Self {
             state: AppState::Running,
             layout_clone: AppLayout::empty(),
             selected_tab: SelectedTab::None,
+            zoomed: false,
             cpu_scrollbar_state: HorizontalScrollbarState {
                 state: ScrollbarState::new(0),
                 pos: 0,
                 max_scroll: 0,
                 real_content_length: 0,
             },
+            memory_scrollbar_state: VerticalScrollbarState {
+                state: ScrollbarState::new(0),
+                pos: 0,
+                max_scroll: 0,
+            },
             processes_scrollbar_state: VerticalScrollbarState {
                 state: ScrollbarState::new(0),
                 pos: 0,
                 max_scroll: 0,
             },
-            process_sort_state: None,
+            process_sort_state: Vec::new(),
+            process_columns: config.process_columns.clone(),
+            io_mode: false,
+            sort_before_io_mode: Vec::new(),
+            proc_stats: ProcStatCache::default(),
+            process_tree_state: ProcessTreeState::default(),
+            process_group_state: ProcessGroupState::default(),
+            process_pins: ProcessPins::default(),
+            process_marks: ProcessMarks::default(),
+            process_selection: ProcessSelection::new(),
+            process_view: ProcessView::empty(),
+            process_view_stale: true,
+            user_names: UserNames::default(),
+            visible_pids: Vec::new(),
+            process_filter: ProcessFilter::new(),
+            views: config.views.clone().into_iter().collect(),
+            dialog: None,
+            status_message: None,
             disks_scrollbar_state: VerticalScrollbarState {
                 state: ScrollbarState::new(0),
                 pos: 0,
@@ -228,6 +360,15 @@ impl App {
                 pos: 0,
                 max_scroll: 0,
             },
+            meminfo: None,
+            memory_history: MemoryHistory::new(config.memory_history_seconds),
+            swap_info: SwapInfo::empty(),
+            vmstat: VmStatSampler::new(),
+            pressure_history: PressureHistory::new(),
+            pressure_cgroup: None,
+            cgroup_monitor: CgroupMonitor::default(),
+            cgroup_mode: config.cgroup_limits,
+            config,
         }
     }
 
@@ -246,12 +387,38 @@ impl App {
             tokio::select! {
                 _ = refresh_ticker.tick() => {
                     sys.refresh_all();
+                    self.meminfo = MemInfo::read();
+                    self.memory_history.refresh(sys, self.meminfo.as_ref());
+                    self.swap_info = SwapInfo::read(self.meminfo.as_ref());
+                    self.vmstat.refresh();
+                    self.refresh_pressure();
+                    if self.cgroup_mode {
+                        self.cgroup_monitor.refresh();
+                    }
+                    self.refresh_proc_stats(sys);
+                    self.user_names.refresh(sys);
+                    self.process_marks.refresh(sys);
+                    let exited = self.process_pins.refresh(sys);
+                    if !exited.is_empty() {
+                        self.set_status(
+                            format!("Pinned process exited: {}", exited.join(", ")),
+                            false,
+                        );
+                    }
+                    self.process_view_stale = true;
+                    match &mut self.dialog {
+                        Some(Dialog::Details(details_view)) => details_view.refresh(sys),
+                        Some(Dialog::Threads(threads_view)) => threads_view.refresh(),
+                        Some(Dialog::OpenFiles(open_files_view)) => open_files_view.refresh(),
+                        Some(Dialog::Connections(connections_view)) => connections_view.refresh(sys),
+                        _ => {}
+                    }
                 }
                 _ = draw_ticker.tick() => {
                     terminal.draw(|frame| self.draw(frame, sys))?;
                 }
                 Some(message) = rx.recv() => {
-                    self.handle_events(&message);
+                    self.handle_events(&message, sys);
                 }
             }
         }
@@ -260,42 +427,259 @@ impl App {
         Ok(())
     }
 
-    fn handle_events(&mut self, message: &InputMessage) {
+    fn handle_events(&mut self, message: &InputMessage, sys: &System) {
+        // Moving the cursor only changes the selection, other input can change what's listed
+        let moves_cursor = match message {
+            InputMessage::KeyPress(code) => {
+                self.dialog.is_none()
+                    && !self.process_filter.editing
+                    && matches!(
+                        code,
+                        KeyCode::Char('j' | 'k' | 'h' | 'l')
+                            | KeyCode::Up
+                            | KeyCode::Down
+                            | KeyCode::Left
+                            | KeyCode::Right
+                    )
+            }
+            InputMessage::MouseScroll { .. } | InputMessage::MouseMoved { .. } => true,
+            InputMessage::ShiftDigit(_) | InputMessage::Quit => false,
+        };
+        if !moves_cursor {
+            self.process_view_stale = true;
+        }
+
+        if let Some(dialog) = &mut self.dialog {
+            if let InputMessage::KeyPress(code) = message {
+                match dialog {
+                    Dialog::Signal(signal_dialog) => match signal_dialog.handle_key(*code) {
+                        SignalDialogAction::None => {}
+                        SignalDialogAction::Close => self.dialog = None,
+                        SignalDialogAction::Send {
+                            pids,
+                            signal,
+                            signal_name,
+                            tree,
+                        } => {
+                            self.dialog = None;
+                            match send_signal(sys, &pids, signal, tree) {
+                                Result::Ok(count) => self.set_status(
+                                    format!("Sent SIG{} to {} process(es)", signal_name, count),
+                                    false,
+                                ),
+                                Err(err) => self.set_status(err, true),
+                            }
+                        }
+                    },
+                    Dialog::Columns(column_picker) => match column_picker.handle_key(*code) {
+                        ColumnPickerAction::None => {}
+                        ColumnPickerAction::Close => self.dialog = None,
+                        ColumnPickerAction::Save(columns) => {
+                            self.dialog = None;
+                            self.set_process_columns(columns);
+                        }
+                    },
+                    Dialog::Details(details_view) => match details_view.handle_key(*code) {
+                        DetailsViewAction::None => {}
+                        DetailsViewAction::Close => self.dialog = None,
+                    },
+                    Dialog::Threads(threads_view) => match threads_view.handle_key(*code) {
+                        ThreadsViewAction::None => {}
+                        ThreadsViewAction::Close => self.dialog = None,
+                    },
+                    Dialog::OpenFiles(open_files_view) => match open_files_view.handle_key(*code) {
+                        OpenFilesViewAction::None => {}
+                        OpenFilesViewAction::Close => self.dialog = None,
+                    },
+                    Dialog::Connections(connections_view) => {
+                        match connections_view.handle_key(*code) {
+                            ConnectionsViewAction::None => {}
+                            ConnectionsViewAction::Close => self.dialog = None,
+                            ConnectionsViewAction::ShowProcess(pid) => {
+                                self.dialog = None;
+                                self.show_process(sys, pid);
+                            }
+                        }
+                    }
+                    Dialog::Scheduling(scheduling_dialog) => {
+                        match scheduling_dialog.handle_key(*code) {
+                            SchedulingDialogAction::None => {}
+                            SchedulingDialogAction::Close => self.dialog = None,
+                            SchedulingDialogAction::Apply => {
+                                let result = scheduling_dialog.apply();
+                                self.dialog = None;
+                                match result {
+                                    Result::Ok(message) => self.set_status(message, false),
+                                    Err(err) => self.set_status(err, true),
+                                }
+                            }
+                        }
+                    }
+                }
+            } else if let InputMessage::Quit = message {
+                self.quit();
+            }
+            return;
+        }
+
+        if self.process_filter.editing {
+            match message {
+                InputMessage::KeyPress(code) => match code {
+                    KeyCode::Char(c) => self.process_filter.push(*c),
+                    KeyCode::Backspace => self.process_filter.pop(),
+                    KeyCode::Tab => self.process_filter.toggle_mode(),
+                    KeyCode::Enter => self.process_filter.editing = false,
+                    KeyCode::Esc => self.process_filter.clear(),
+                    _ => {}
+                },
+                InputMessage::ShiftDigit(digit) => self.process_filter.push(*digit),
+                InputMessage::Quit => self.quit(),
+                _ => {}
+            }
+            return;
+        }
+
         match message {
             InputMessage::KeyPress(code) => match code {
+                KeyCode::Char('/') if self.selected_tab.is_processes() => {
+                    self.process_filter.editing = true;
+                }
+                KeyCode::Char('v') if self.selected_tab.is_processes() => self.next_view(),
+                // An active filter is cleared before Esc quits
+                KeyCode::Esc if self.process_filter.is_active() => self.process_filter.clear(),
+                KeyCode::Char('q') | KeyCode::Esc => self.quit(),
                 KeyCode::Char('l') | KeyCode::Right => self.scroll_right(),
                 KeyCode::Char('h') | KeyCode::Left => self.scroll_left(),
                 KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
                 KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
+                KeyCode::Char('z') => self.zoomed = !self.zoomed,
+                KeyCode::Char('L') => self.toggle_cgroup_mode(sys),
                 KeyCode::Tab => self.next_tab(),
                 KeyCode::BackTab => self.prev_tab(),
-                KeyCode::Char('1') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::User)
+                // Digits sort by the visible column at that position
+                KeyCode::Char(digit @ '1'..='9') if self.selected_tab.is_processes() => {
+                    let index = *digit as usize - '1' as usize;
+                    if let Some(column) = self.visible_process_columns().get(index) {
+                        self.toggle_sort_column(*column)
+                    }
                 }
-                KeyCode::Char('2') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::PID)
+                // Shift+digit adds the column as a further sort key
+                KeyCode::Char(symbol)
+                    if self.selected_tab.is_processes() && SHIFTED_DIGITS.contains(*symbol) =>
+                {
+                    if let Some(index) = SHIFTED_DIGITS.chars().position(|c| c == *symbol) {
+                        self.toggle_secondary_sort_key(index);
+                    }
                 }
-                KeyCode::Char('3') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::PPID)
+                // Reset sorting if 'r' is pressed
+                KeyCode::Char('r') if self.selected_tab.is_processes() => {
+                    self.process_sort_state.clear();
                 }
-                KeyCode::Char('4') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::CPU)
+                KeyCode::Char('P') => self.next_pressure_cgroup(),
+                KeyCode::Char('s') if self.selected_tab.is_processes() => {
+                    if !self.process_marks.is_empty() {
+                        self.dialog = Some(Dialog::Signal(SignalDialog::for_marked(
+                            self.process_marks.pids(),
+                        )));
+                    } else if let Some(process) =
+                        self.process_selection.pid.and_then(|pid| sys.process(pid))
+                    {
+                        self.dialog = Some(Dialog::Signal(SignalDialog::new(
+                            process.pid(),
+                            process.name().to_string(),
+                        )));
+                    }
                 }
-                KeyCode::Char('5') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::Memory)
+                KeyCode::Char(' ') if self.selected_tab.is_processes() => {
+                    if let Some(process) =
+                        self.process_selection.pid.and_then(|pid| sys.process(pid))
+                    {
+                        self.process_marks.toggle(process);
+                    }
+                    self.process_selection.select_next();
                 }
-                KeyCode::Char('6') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::Time)
+                KeyCode::Char('m') if self.selected_tab.is_processes() => {
+                    for process in sys.processes().values() {
+                        if self
+                            .process_filter
+                            .matches(process, &self.user_names, &self.proc_stats)
+                        {
+                            self.process_marks.mark(process);
+                        }
+                    }
                 }
-                KeyCode::Char('7') if self.selected_tab.is_processes() => {
-                    self.toggle_sort_column(ProcessColumn::Command)
+                KeyCode::Char('u') if self.selected_tab.is_processes() => {
+                    self.process_marks.clear();
                 }
-                // Reset sorting if 'r' is pressed
-                KeyCode::Char('r') if self.selected_tab.is_processes() => {
-                    self.process_sort_state = None;
+                KeyCode::Char('e') if self.selected_tab.is_processes() => self.export_marked(sys),
+                KeyCode::Char('i') if self.selected_tab.is_processes() => self.toggle_io_mode(),
+                KeyCode::Char('p') if self.selected_tab.is_processes() => self.toggle_pin(sys),
+                KeyCode::Char('c') if self.selected_tab.is_processes() => {
+                    self.dialog = Some(Dialog::Columns(ColumnPicker::new(&self.process_columns)));
+                }
+                KeyCode::Enter if self.selected_tab.is_processes() => {
+                    if let Some(pid) = self.process_selection.pid {
+                        self.dialog = Some(Dialog::Details(Box::new(DetailsView::new(sys, pid))));
+                    } else if let Some(group) = &self.process_selection.group {
+                        self.process_group_state.toggle(group);
+                    }
+                }
+                KeyCode::Char('H') if self.selected_tab.is_processes() => {
+                    if let Some(process) =
+                        self.process_selection.pid.and_then(|pid| sys.process(pid))
+                    {
+                        self.dialog = Some(Dialog::Threads(ThreadsView::new(
+                            process.pid(),
+                            process.name().to_string(),
+                        )));
+                    }
+                }
+                KeyCode::Char('o') if self.selected_tab.is_processes() => {
+                    if let Some(process) =
+                        self.process_selection.pid.and_then(|pid| sys.process(pid))
+                    {
+                        self.dialog = Some(Dialog::OpenFiles(OpenFilesView::new(
+                            process.pid(),
+                            process.name().to_string(),
+                        )));
+                    }
+                }
+                KeyCode::Enter if self.selected_tab.is_network() => {
+                    self.dialog = Some(Dialog::Connections(ConnectionsView::new(sys)));
+                }
+                KeyCode::Char('n') if self.selected_tab.is_processes() => {
+                    self.open_scheduling_dialog(sys)
+                }
+                KeyCode::Char('t') if self.selected_tab.is_processes() => {
+                    self.process_tree_state.enabled = !self.process_tree_state.enabled;
+                    self.process_group_state.grouping = None;
+                }
+                KeyCode::Char('g') if self.selected_tab.is_processes() => {
+                    self.process_group_state.cycle();
+                    self.process_tree_state.enabled = false;
+                }
+                KeyCode::Char('a') if self.selected_tab.is_processes() => {
+                    self.process_tree_state.show_subtree_totals =
+                        !self.process_tree_state.show_subtree_totals;
+                }
+                KeyCode::Char('-') if self.selected_tab.is_processes() => {
+                    self.collapse_selected(sys)
+                }
+                KeyCode::Char('+') | KeyCode::Char('=') if self.selected_tab.is_processes() => {
+                    if let Some(group) = &self.process_selection.group {
+                        self.process_group_state.expand(group);
+                    } else if let Some(pid) = self.process_selection.pid {
+                        self.process_tree_state.expand(pid);
+                    }
                 }
                 _ => {}
             },
+            InputMessage::ShiftDigit(digit) if self.selected_tab.is_processes() => {
+                if let Some(index) = digit.to_digit(10).and_then(|digit| digit.checked_sub(1)) {
+                    self.toggle_secondary_sort_key(index as usize);
+                }
+            }
+            InputMessage::ShiftDigit(_) => {}
             InputMessage::MouseScroll { direction } => match direction {
                 MouseScrollDirection::Up => self.scroll_up(),
                 MouseScrollDirection::Down => self.scroll_down(),
@@ -307,19 +691,347 @@ impl App {
         }
     }
 
+    /// Steps the pressure panel from the system to each cgroup with pressure files and back.
+    fn next_pressure_cgroup(&mut self) {
+        let cgroups = list_pressure_cgroups();
+        let next = match &self.pressure_cgroup {
+            Some(current) => cgroups
+                .iter()
+                .position(|cgroup| cgroup == current)
+                .map(|i| i + 1),
+            None => Some(0),
+        };
+        self.pressure_cgroup = next.and_then(|i| cgroups.get(i)).cloned();
+        self.refresh_pressure();
+    }
+
+    fn refresh_pressure(&mut self) {
+        let cgroup = self
+            .selected_cgroup()
+            .or_else(|| self.pressure_cgroup.clone());
+        self.pressure_history.refresh(cgroup.as_deref());
+    }
+
+    /// Cgroup of the selected group row or process while processes are grouped by cgroup, which
+    /// takes precedence over the one picked with `P`. The root cgroup's pressure is the system's,
+    /// so it's left to `/proc/pressure`.
+    fn selected_cgroup(&self) -> Option<String> {
+        if self.process_group_state.grouping != Some(ProcessGrouping::Cgroup) {
+            return None;
+        }
+        let cgroup = match &self.process_selection.group {
+            Some(group) => Some(group.as_str()),
+            None => self
+                .process_selection
+                .pid
+                .and_then(|pid| self.proc_stats.cgroup(pid)),
+        };
+        cgroup
+            .filter(|cgroup| !matches!(*cgroup, "/" | "unknown"))
+            .map(str::to_string)
+    }
+
+    /// Switches to the Processes tab with the process selected, clearing the filter, expanding
+    /// its group or its collapsed ancestors if they hide it.
+    fn show_process(&mut self, sys: &System, pid: Pid) {
+        let Some(process) = sys.process(pid) else {
+            self.set_status(format!("Process {} has exited", pid), true);
+            return;
+        };
+
+        if self.process_filter.is_active()
+            && !self
+                .process_filter
+                .matches(process, &self.user_names, &self.proc_stats)
+        {
+            self.process_filter.clear();
+        }
+        match self.process_group_state.grouping {
+            Some(grouping) => {
+                let key = group_key(process, grouping, &self.user_names, &self.proc_stats);
+                self.process_group_state.expand(&key);
+            }
+            None => {
+                let mut parent = process.parent();
+                while let Some(ancestor) = parent {
+                    self.process_tree_state.expand(ancestor);
+                    parent = sys.process(ancestor).and_then(|process| process.parent());
+                }
+            }
+        }
+
+        self.process_selection.pid = Some(pid);
+        self.process_selection.group = None;
+        self.selected_tab = SelectedTab::Processes;
+        self.process_view_stale = true;
+    }
+
+    /// Opens the scheduling dialog for the marked processes, or the selected one without marks.
+    fn open_scheduling_dialog(&mut self, sys: &System) {
+        let marked = self.process_marks.pids();
+        let target = match marked.first() {
+            Some(pid) => sys.process(*pid),
+            None => self.process_selection.pid.and_then(|pid| sys.process(pid)),
+        };
+        let Some(process) = target else {
+            return;
+        };
+
+        let dialog = if marked.is_empty() {
+            SchedulingDialog::new(process.pid(), process.name().to_string())
+        } else {
+            SchedulingDialog::for_marked(marked, process.name().to_string())
+        };
+        match dialog {
+            Result::Ok(dialog) => self.dialog = Some(Dialog::Scheduling(dialog)),
+            Err(err) => self.set_status(
+                format!(
+                    "Failed to read scheduling of {} ({}): {}",
+                    process.name(),
+                    process.pid(),
+                    err
+                ),
+                true,
+            ),
+        }
+    }
+
+    /// Writes the marked processes, or the selected one without marks, to a CSV file.
+    fn export_marked(&mut self, sys: &System) {
+        let pids = if self.process_marks.is_empty() {
+            self.process_selection.pid.into_iter().collect()
+        } else {
+            self.process_marks.pids()
+        };
+        if pids.is_empty() {
+            return;
+        }
+
+        match export_processes_to_file(sys, &self.user_names, &pids) {
+            Result::Ok((path, count)) => {
+                self.set_status(format!("Exported {} process(es) to {}", count, path), false)
+            }
+            Err(err) => self.set_status(format!("Failed to export processes: {}", err), true),
+        }
+    }
+
+    fn toggle_pin(&mut self, sys: &System) {
+        // Exited pinned processes have no PID to select, they're unpinned by position
+        if let Some(index) = self.process_view.pinned_index(&self.process_selection) {
+            if self.process_selection.pid.is_none() {
+                self.process_pins.remove(index);
+                return;
+            }
+        }
+        let Some(process) = self.process_selection.pid.and_then(|pid| sys.process(pid)) else {
+            return;
+        };
+
+        let text = if self.process_pins.toggle(process) {
+            format!("Pinned {} ({})", process.name(), process.pid())
+        } else {
+            format!("Unpinned {} ({})", process.name(), process.pid())
+        };
+        self.set_status(text, false);
+    }
+
+    /// Collapses the selected subtree, or in grouping mode the group of the selected row.
+    fn collapse_selected(&mut self, sys: &System) {
+        if let Some(group) = &self.process_selection.group {
+            self.process_group_state.collapse(group);
+            return;
+        }
+        let Some(pid) = self.process_selection.pid else {
+            return;
+        };
+
+        match self.process_group_state.grouping {
+            Some(grouping) => {
+                if let Some(process) = sys.process(pid) {
+                    let key = group_key(process, grouping, &self.user_names, &self.proc_stats);
+                    self.process_group_state.collapse(&key);
+                    // The process is hidden now, its group row takes the cursor
+                    self.process_selection.select_group(key);
+                }
+            }
+            None => self.process_tree_state.collapse(pid),
+        }
+    }
+
+    /// Applies the view after the active one, the filter is cleared after the last view.
+    fn next_view(&mut self) {
+        if self.views.is_empty() {
+            self.set_status("No views configured".to_string(), true);
+            return;
+        }
+
+        let next = match &self.process_filter.view {
+            Some(view) => self
+                .views
+                .iter()
+                .position(|(name, _)| name == view)
+                .map_or(0, |index| index + 1),
+            None => 0,
+        };
+        match self.views.get(next) {
+            Some((name, query)) => self.process_filter.apply_view(name, query),
+            None => self.process_filter.clear(),
+        }
+    }
+
+    /// Reads the `/proc` data only the visible or sorted columns need.
+    fn refresh_proc_stats(&mut self, sys: &System) {
+        let columns = || {
+            self.visible_process_columns()
+                .iter()
+                .chain(self.process_sort_state.iter().map(|(column, _)| column))
+        };
+        let read_stat = columns().any(|column| column.needs_stat());
+        let read_swap = columns().any(|column| column.needs_swap());
+        let read_cgroup = matches!(
+            self.process_group_state.grouping,
+            Some(ProcessGrouping::Cgroup | ProcessGrouping::Container)
+        ) || self.process_filter.needs_cgroup()
+            || columns().any(|column| column.needs_cgroup());
+        self.proc_stats
+            .refresh(sys, read_stat, read_swap, read_cgroup);
+
+        if self.needs_smaps() {
+            self.proc_stats
+                .refresh_smaps(sys, self.visible_pids.clone());
+        } else {
+            self.proc_stats.clear_smaps();
+        }
+    }
+
+    /// Whether a visible or sorted column comes from `smaps_rollup`.
+    fn needs_smaps(&self) -> bool {
+        self.visible_process_columns()
+            .iter()
+            .chain(self.process_sort_state.iter().map(|(column, _)| column))
+            .any(|column| column.needs_smaps())
+    }
+
+    fn visible_process_columns(&self) -> &[ProcessColumn] {
+        if self.io_mode {
+            &ProcessColumn::IO
+        } else {
+            &self.process_columns
+        }
+    }
+
+    /// Switches to the IO columns ranked by total throughput, and back to the previous sort.
+    fn toggle_io_mode(&mut self) {
+        self.io_mode = !self.io_mode;
+        if self.io_mode {
+            self.sort_before_io_mode = std::mem::replace(
+                &mut self.process_sort_state,
+                vec![(ProcessColumn::IoRate, SortDirection::Descending)],
+            );
+        } else {
+            self.process_sort_state = std::mem::take(&mut self.sort_before_io_mode);
+        }
+    }
+
+    fn toggle_cgroup_mode(&mut self, sys: &System) {
+        if self.cgroup_mode {
+            self.cgroup_mode = false;
+            // The CPU usage sample would span the whole time the mode was off
+            self.cgroup_monitor = CgroupMonitor::default();
+            self.set_status("Showing host memory and CPU usage".to_string(), false);
+            return;
+        }
+
+        self.cgroup_monitor.refresh();
+        let Some(limits) = &self.cgroup_monitor.limits else {
+            self.set_status(
+                "Failed to read the limits of reson's cgroup".to_string(),
+                true,
+            );
+            return;
+        };
+        let version = if limits.is_v2 { "v2" } else { "v1" };
+        let text = if limits.is_limited(sys.cpus().len(), sys.total_memory()) {
+            format!(
+                "Showing usage against the limits of cgroup {} ({})",
+                limits.path, version
+            )
+        } else {
+            format!(
+                "Showing usage of cgroup {} ({}), it has no limits tighter than the host",
+                limits.path, version
+            )
+        };
+        self.cgroup_mode = true;
+        self.set_status(text, false);
+    }
+
+    /// Limits of reson's cgroup while the gauges show usage against them.
+    fn cgroup_monitor(&self) -> Option<&CgroupMonitor> {
+        (self.cgroup_mode && self.cgroup_monitor.limits.is_some()).then_some(&self.cgroup_monitor)
+    }
+
+    fn set_process_columns(&mut self, columns: Vec<ProcessColumn>) {
+        if self.io_mode {
+            self.toggle_io_mode();
+        }
+        self.process_columns = columns.clone();
+        self.config.process_columns = columns;
+        match self.config.save() {
+            Result::Ok(path) => {
+                self.set_status(format!("Saved columns to {}", path.display()), false)
+            }
+            Err(err) => self.set_status(format!("Failed to save columns: {}", err), true),
+        }
+    }
+
+    fn set_status(&mut self, text: String, is_error: bool) {
+        self.status_message = Some(StatusMessage {
+            text,
+            is_error,
+            created: Instant::now(),
+        });
+    }
+
+    /// Sorts by the column alone, or cycles its direction when it already is the first key.
     fn toggle_sort_column(&mut self, column: ProcessColumn) {
-        match &self.process_sort_state {
-            Some((current_column, direction)) if *current_column == column => match direction {
+        match self.process_sort_state.first() {
+            Some((current_column, SortDirection::Ascending)) if *current_column == column => {
+                self.process_sort_state[0].1 = SortDirection::Descending;
+            }
+            Some((current_column, SortDirection::Descending)) if *current_column == column => {
+                self.process_sort_state.remove(0);
+            }
+            _ => self.process_sort_state = vec![(column, SortDirection::Ascending)],
+        }
+    }
+
+    /// Adds the visible column at `index` as a further sort key, for Shift+1 to Shift+9.
+    fn toggle_secondary_sort_key(&mut self, index: usize) {
+        if let Some(column) = self.visible_process_columns().get(index) {
+            self.toggle_secondary_sort_column(*column)
+        }
+    }
+
+    /// Appends the column as the last sort key, or cycles its direction if it's already a key.
+    fn toggle_secondary_sort_column(&mut self, column: ProcessColumn) {
+        let position = self
+            .process_sort_state
+            .iter()
+            .position(|(sort_column, _)| *sort_column == column);
+
+        match position {
+            Some(index) => match self.process_sort_state[index].1 {
                 SortDirection::Ascending => {
-                    self.process_sort_state = Some((column, SortDirection::Descending));
+                    self.process_sort_state[index].1 = SortDirection::Descending
                 }
                 SortDirection::Descending => {
-                    self.process_sort_state = None;
+                    self.process_sort_state.remove(index);
                 }
             },
-            _ => {
-                self.process_sort_state = Some((column, SortDirection::Ascending));
-            }
+            None => self
+                .process_sort_state
+                .push((column, SortDirection::Ascending)),
         }
     }
 
@@ -333,6 +1045,16 @@ impl App {
                 .cpu_layout,
         ) {
             self.selected_tab = SelectedTab::Cpu;
+        } else if is_within_rect(
+            position,
+            &self
+                .layout_clone
+                .main_layout
+                .cpu_plus_memory_layout
+                .memory_layout
+                .area,
+        ) {
+            self.selected_tab = SelectedTab::Memory;
         } else if is_within_rect(position, &self.layout_clone.main_layout.processes_layout) {
             self.selected_tab = SelectedTab::Processes;
         } else if is_within_rect(position, &self.layout_clone.main_layout.disk_layout) {
@@ -357,8 +1079,13 @@ impl App {
     }
 
     fn scroll_down(&mut self) {
+        if self.selected_tab.is_memory() {
+            self.memory_scrollbar_state.scroll_next();
+            return;
+        }
+
         if self.selected_tab.is_processes() {
-            self.processes_scrollbar_state.scroll_next();
+            self.process_selection.select_next();
             return;
         }
 
@@ -369,13 +1096,17 @@ impl App {
 
         if self.selected_tab.is_network() {
             self.networks_scrollbar_state.scroll_next();
-            return;
         }
     }
 
     fn scroll_up(&mut self) {
+        if self.selected_tab.is_memory() {
+            self.memory_scrollbar_state.scroll_prev();
+            return;
+        }
+
         if self.selected_tab.is_processes() {
-            self.processes_scrollbar_state.scroll_prev();
+            self.process_selection.select_prev();
             return;
         }
 
@@ -386,7 +1117,6 @@ impl App {
 
         if self.selected_tab.is_network() {
             self.networks_scrollbar_state.scroll_prev();
-            return;
         }
     }
 
@@ -403,27 +1133,55 @@ impl App {
     }
 
     fn draw(&mut self, frame: &mut Frame, sys: &System) {
-        let layout = prepare_layout(frame);
+        let zoomed_panel = if self.zoomed {
+            self.selected_tab.zoomed_panel()
+        } else {
+            None
+        };
+        let layout = prepare_layout(frame, zoomed_panel);
         self.layout_clone = layout.clone();
 
         self.render_main_layout(frame, sys, &layout);
-        self.render_footer(frame, &layout.footer_area);
+        self.render_footer(frame, sys, &layout.footer_area);
+        self.render_dialog(frame);
     }
 
     fn render_main_layout(&mut self, frame: &mut Frame, sys: &System, app_layout: &AppLayout) {
-        self.render_cpu(
-            frame,
-            sys,
-            &app_layout.main_layout.cpu_plus_memory_layout.cpu_layout,
-        );
-        self.render_memory_gauges(
-            frame,
-            sys,
-            &app_layout.main_layout.cpu_plus_memory_layout.memory_layout,
-        );
-        self.render_processes(frame, sys, &app_layout.main_layout.processes_layout);
-        self.render_disks(frame, sys, &app_layout.main_layout.disk_layout);
-        self.render_networks(frame, sys, &app_layout.main_layout.network_layout);
+        let main_layout = &app_layout.main_layout;
+
+        // Panels hidden by the zoomed view get an empty area
+        if !main_layout.cpu_plus_memory_layout.cpu_layout.is_empty() {
+            self.render_cpu(frame, sys, &main_layout.cpu_plus_memory_layout.cpu_layout);
+        }
+        if !main_layout
+            .cpu_plus_memory_layout
+            .memory_layout
+            .area
+            .is_empty()
+        {
+            self.render_memory_gauges(
+                frame,
+                sys,
+                &main_layout.cpu_plus_memory_layout.memory_layout,
+            );
+        }
+        if !main_layout
+            .cpu_plus_memory_layout
+            .pressure_layout
+            .area
+            .is_empty()
+        {
+            self.render_pressure(frame, &main_layout.cpu_plus_memory_layout.pressure_layout);
+        }
+        if !main_layout.processes_layout.is_empty() {
+            self.render_processes(frame, sys, &main_layout.processes_layout);
+        }
+        if !main_layout.disk_layout.is_empty() {
+            self.render_disks(frame, sys, &main_layout.disk_layout);
+        }
+        if !main_layout.network_layout.is_empty() {
+            self.render_networks(frame, sys, &main_layout.network_layout);
+        }
     }
 
     fn render_cpu(&mut self, frame: &mut Frame, sys: &System, cpu_layout: &Rect) {
@@ -431,6 +1189,7 @@ impl App {
 
         let cpu_barchart = create_cpu_barchart(
             sys,
+            self.cgroup_monitor(),
             cpu_layout.width.into(),
             self.cpu_scrollbar_state.pos,
             is_selected,
@@ -457,24 +1216,140 @@ impl App {
         );
     }
 
-    fn render_memory_gauges(&self, frame: &mut Frame, sys: &System, memory_layout: &MemoryLayout) {
-        let memory_gauges = create_memory_gauges(sys);
-        frame.render_widget(memory_gauges.ram_gauge, memory_layout.ram_layout);
-        frame.render_widget(memory_gauges.swap_gauge, memory_layout.swap_layout);
+    fn render_memory_gauges(
+        &mut self,
+        frame: &mut Frame,
+        sys: &System,
+        memory_layout: &MemoryLayout,
+    ) {
+        let is_selected = self.selected_tab.is_memory();
+
+        // Focusing the memory panel swaps the gauges for the detailed breakdown,
+        // the zoomed view shows the gauges, the breakdown and the history chart together
+        if is_selected || memory_layout.history_layout.is_some() {
+            self.render_memory_breakdown(frame, &memory_layout.breakdown_layout, is_selected);
+        }
+
+        if let Some(history_layout) = memory_layout.history_layout {
+            let points = self.memory_history.chart_points();
+            let history_chart = create_memory_history_chart(&self.memory_history, &points);
+            frame.render_widget(history_chart, history_layout);
+        } else if is_selected {
+            return;
+        }
+
+        let cgroup_limits = self
+            .cgroup_monitor()
+            .and_then(|monitor| monitor.limits.as_ref());
+        frame.render_widget(
+            create_memory_gauge(sys, cgroup_limits),
+            memory_layout.ram_layout,
+        );
+
+        let swap_widget = create_swap_widget(sys, &self.swap_info);
+        frame.render_widget(swap_widget.block, memory_layout.swap_layout.area);
+        let swap_layout = &memory_layout.swap_layout;
+        match swap_widget.gauge {
+            Some(gauge) => {
+                frame.render_widget(gauge, swap_layout.gauge);
+                frame.render_widget(swap_widget.devices, swap_layout.devices);
+            }
+            None => frame.render_widget(
+                swap_widget.devices,
+                swap_layout.gauge.union(swap_layout.devices),
+            ),
+        }
+    }
+
+    fn render_memory_breakdown(
+        &mut self,
+        frame: &mut Frame,
+        breakdown_layout: &Rect,
+        is_selected: bool,
+    ) {
+        let memory_breakdown = create_memory_breakdown(
+            self.meminfo.as_ref(),
+            self.vmstat.rates.as_ref(),
+            breakdown_layout.width.into(),
+            breakdown_layout.height.into(),
+            self.memory_scrollbar_state.pos,
+            is_selected,
+        );
+        frame.render_widget(memory_breakdown.chart, *breakdown_layout);
+
+        self.memory_scrollbar_state
+            .set_values(memory_breakdown.max_scroll);
+        self.memory_scrollbar_state.current_pos_scroll_update();
+
+        frame.render_stateful_widget(
+            get_vertical_scrollbar(),
+            *breakdown_layout,
+            &mut self.memory_scrollbar_state.state,
+        );
+    }
+
+    fn render_pressure(&self, frame: &mut Frame, pressure_layout: &PressureLayout) {
+        let pressure_widget =
+            create_pressure_widget(&self.pressure_history, pressure_layout.area.width.into());
+        frame.render_widget(pressure_widget.block, pressure_layout.area);
+
+        for (row, row_layout) in [
+            (pressure_widget.cpu, &pressure_layout.cpu),
+            (pressure_widget.memory, &pressure_layout.memory),
+            (pressure_widget.io, &pressure_layout.io),
+        ] {
+            frame.render_widget(row.summary, row_layout.summary);
+            frame.render_widget(row.sparkline, row_layout.sparkline);
+        }
     }
 
     fn render_processes(&mut self, frame: &mut Frame, sys: &System, processes_layout: &Rect) {
+        if self.process_view_stale {
+            // A filter that just started using containers can't wait for the next refresh
+            if self.process_filter.needs_cgroup() && !self.proc_stats.has_cgroups() {
+                self.proc_stats.refresh_cgroups(sys);
+            }
+            self.process_view = build_process_view(
+                sys,
+                self.visible_process_columns(),
+                &self.process_sort_state,
+                &self.process_tree_state,
+                &self.process_group_state,
+                &self.process_filter,
+                &self.process_pins,
+                &self.process_marks,
+                &self.proc_stats,
+                &self.user_names,
+            );
+            self.process_view_stale = false;
+        }
+
         let is_selected = self.selected_tab.is_processes();
         let processes_table = create_processes_table(
-            sys,
+            &self.process_view,
+            processes_layout.width.into(),
             processes_layout.height.into(),
             self.processes_scrollbar_state.pos,
             is_selected,
-            self.process_sort_state,
+            &mut self.process_selection,
         );
 
-        frame.render_widget(processes_table.chart, *processes_layout);
+        // Rows scrolled into view are read right away rather than on the next refresh
+        if processes_table.visible_pids != self.visible_pids {
+            if self.needs_smaps() {
+                self.proc_stats
+                    .request_smaps(processes_table.visible_pids.clone());
+            }
+            self.visible_pids = processes_table.visible_pids.clone();
+        }
 
+        frame.render_stateful_widget(
+            processes_table.chart.widths(&processes_table.widths),
+            *processes_layout,
+            &mut self.process_selection.table_state,
+        );
+
+        self.processes_scrollbar_state.pos = processes_table.scroll_position;
         self.processes_scrollbar_state
             .set_values(processes_table.max_scroll);
         self.processes_scrollbar_state.current_pos_scroll_update();
@@ -528,12 +1403,138 @@ impl App {
         );
     }
 
-    fn render_footer(&self, frame: &mut Frame, footer_area: &Rect) {
-        let footer_text = if self.selected_tab.is_processes() {
-            "1-7: Sort columns | r: Reset sort | Tab: Next tab | h/j/k/l: Scroll | q: Quit"
+    fn render_dialog(&mut self, frame: &mut Frame) {
+        let Some(dialog) = &mut self.dialog else {
+            return;
+        };
+
+        match dialog {
+            Dialog::Signal(signal_dialog) => {
+                let area = centered_rect(60, 15, frame.size());
+                frame.render_widget(Clear, area);
+                frame.render_widget(create_signal_dialog(signal_dialog), area);
+            }
+            Dialog::Details(details_view) => {
+                let size = frame.size();
+                let area = centered_rect(size.width * 9 / 10, size.height * 9 / 10, size);
+                let details =
+                    create_details_widget(details_view, area.width.into(), area.height.into());
+                frame.render_widget(Clear, area);
+                frame.render_widget(details.chart, area);
+
+                let mut scrollbar_state = ScrollbarState::default()
+                    .content_length(details.max_scroll)
+                    .position(details.scroll_position);
+                frame.render_stateful_widget(get_vertical_scrollbar(), area, &mut scrollbar_state);
+            }
+            Dialog::Threads(threads_view) => {
+                let size = frame.size();
+                let area = centered_rect(76, size.height * 9 / 10, size);
+                let threads = create_threads_table(threads_view, area.height.into());
+                frame.render_widget(Clear, area);
+                frame.render_widget(threads.chart, area);
+
+                let mut scrollbar_state = ScrollbarState::default()
+                    .content_length(threads.max_scroll)
+                    .position(threads.scroll_position);
+                frame.render_stateful_widget(get_vertical_scrollbar(), area, &mut scrollbar_state);
+            }
+            Dialog::OpenFiles(open_files_view) => {
+                let size = frame.size();
+                let area = centered_rect(size.width * 9 / 10, size.height * 9 / 10, size);
+                let open_files =
+                    create_open_files_table(open_files_view, area.width.into(), area.height.into());
+                frame.render_widget(Clear, area);
+                frame.render_widget(open_files.chart.widths(&open_files.widths), area);
+
+                let mut scrollbar_state = ScrollbarState::default()
+                    .content_length(open_files.max_scroll)
+                    .position(open_files.scroll_position);
+                frame.render_stateful_widget(get_vertical_scrollbar(), area, &mut scrollbar_state);
+            }
+            Dialog::Connections(connections_view) => {
+                let size = frame.size();
+                let area = centered_rect(size.width * 9 / 10, size.height * 9 / 10, size);
+                let connections = create_connections_table(
+                    connections_view,
+                    area.width.into(),
+                    area.height.into(),
+                );
+                frame.render_widget(Clear, area);
+                frame.render_widget(connections.chart.widths(&connections.widths), area);
+
+                let mut scrollbar_state = ScrollbarState::default()
+                    .content_length(connections.max_scroll)
+                    .position(connections.scroll_position);
+                frame.render_stateful_widget(get_vertical_scrollbar(), area, &mut scrollbar_state);
+            }
+            Dialog::Columns(column_picker) => {
+                let area = centered_rect(50, column_picker.height(), frame.size());
+                frame.render_widget(Clear, area);
+                frame.render_widget(create_column_picker(column_picker), area);
+            }
+            Dialog::Scheduling(scheduling_dialog) => {
+                let height = scheduling_dialog_height(scheduling_dialog);
+                let area = centered_rect(66, height, frame.size());
+                frame.render_widget(Clear, area);
+                frame.render_widget(create_scheduling_dialog(scheduling_dialog), area);
+            }
+        }
+    }
+
+    fn render_footer(&self, frame: &mut Frame, sys: &System, footer_area: &Rect) {
+        // Results of actions stay in the footer for a few seconds
+        if let Some(status) = &self.status_message {
+            if status.created.elapsed() < STATUS_MESSAGE_DURATION {
+                let style = if status.is_error {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default().fg(Color::Green)
+                };
+                let footer = Block::default()
+                    .title(status.text.as_str())
+                    .title_style(style)
+                    .title_alignment(Alignment::Center);
+                frame.render_widget(footer, *footer_area);
+                return;
+            }
+        }
+
+        let footer_text = if self.process_filter.editing {
+            let mode = match self.process_filter.mode {
+                FilterMode::Substring => "substring",
+                FilterMode::Regex => "regex",
+                FilterMode::Query => "query",
+            };
+            let error = self
+                .process_filter
+                .error
+                .as_ref()
+                .map_or(String::new(), |error| format!(" ({})", error));
+            format!(
+                "Filter [{}]: {}▏{} | Tab: Substring/regex/query | Enter: Done | Esc: Clear",
+                mode, self.process_filter.query, error
+            )
+        } else if self.selected_tab.is_processes() {
+            let selected = match &self.process_selection.group {
+                Some(group) => format!("group {}", group),
+                None => self
+                    .process_selection
+                    .pid
+                    .and_then(|pid| sys.process(pid))
+                    .map_or("none".to_string(), |process| {
+                        format!("{} ({})", process.name(), process.pid())
+                    }),
+            };
+            format!(
+                "Selected: {} | Enter: Details | H: Threads | o: Open files | p: Pin | Space: Mark | m: Mark all | u: Unmark all | e: Export | /: Filter | v: Views | s: Signal | n: Nice/affinity | c: Columns | i: IO mode | 1-9: Sort | Shift+1-9 (US layout): Add sort key | r: Reset sort | t: Tree | g: Group | +/-: Expand/collapse | a: Subtree totals | Tab: Next tab | j/k: Select | z: Zoom | q: Quit",
+                selected
+            )
+        } else if self.selected_tab.is_network() {
+            "Enter: Connections | Tab: Next tab | h/j/k/l: Scroll | z: Zoom | q: Quit".to_string()
         } else {
             // Regular footer text
-            "Tab: Next tab | h/j/k/l: Scroll | q: Quit"
+            "Tab: Next tab | h/j/k/l: Scroll | z: Zoom | L: Cgroup limits | q: Quit".to_string()
         };
 
         let footer = Block::default()
@@ -547,22 +1548,25 @@ async fn read_input_events(tx: Sender<InputMessage>) {
     loop {
         if let Result::Ok(event) = event::read() {
             match event {
-                Event::Key(key) => {
-                    if key.kind == KeyEventKind::Press {
-                        let msg = match key.code {
-                            KeyCode::Char('q') | KeyCode::Esc => InputMessage::Quit,
-                            KeyCode::Char('c') => {
-                                if key.modifiers == KeyModifiers::CONTROL {
-                                    InputMessage::Quit
-                                } else {
-                                    InputMessage::KeyPress(key.code)
-                                }
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    // q and Esc are handled by the app, open dialogs use them to close
+                    let msg = match key.code {
+                        KeyCode::Char('c') => {
+                            if key.modifiers == KeyModifiers::CONTROL {
+                                InputMessage::Quit
+                            } else {
+                                InputMessage::KeyPress(key.code)
                             }
-                            code => InputMessage::KeyPress(code),
-                        };
-                        if tx.send(msg).await.is_err() {
-                            return;
                         }
+                        KeyCode::Char(digit @ '1'..='9')
+                            if key.modifiers.contains(KeyModifiers::SHIFT) =>
+                        {
+                            InputMessage::ShiftDigit(digit)
+                        }
+                        code => InputMessage::KeyPress(code),
+                    };
+                    if tx.send(msg).await.is_err() {
+                        return;
                     }
                 }
                 Event::Mouse(mouse) => {
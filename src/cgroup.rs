@@ -0,0 +1,442 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+// cgroup v1 reports "no limit" as the largest page aligned 64-bit value
+const V1_UNLIMITED: u64 = 1 << 62;
+
+/// A cgroup hierarchy mounted in reson's mount namespace.
+struct CgroupMount {
+    mount_point: PathBuf,
+    // Cgroup the mount starts at, `/` except for mounts made inside containers
+    root: String,
+    is_v2: bool,
+    controllers: Vec<String>,
+}
+
+fn read_mounts() -> Vec<CgroupMount> {
+    fs::read_to_string("/proc/self/mountinfo")
+        .map(|mountinfo| parse_mountinfo(&mountinfo))
+        .unwrap_or_default()
+}
+
+fn parse_mountinfo(mountinfo: &str) -> Vec<CgroupMount> {
+    // id parent major:minor root mount-point options [optional...] - type source super-options
+    mountinfo
+        .lines()
+        .filter_map(|line| {
+            let (fields, rest) = line.split_once(" - ")?;
+            let fields: Vec<&str> = fields.split_whitespace().collect();
+            let rest: Vec<&str> = rest.split_whitespace().collect();
+            let is_v2 = match *rest.first()? {
+                "cgroup2" => true,
+                "cgroup" => false,
+                _ => return None,
+            };
+            Some(CgroupMount {
+                mount_point: PathBuf::from(fields.get(4)?),
+                root: fields.get(3)?.to_string(),
+                is_v2,
+                controllers: rest
+                    .get(2)
+                    .map(|options| options.split(',').map(str::to_string).collect())
+                    .unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Directory of reson's cgroup in the given hierarchy, from the matching line of
+/// `/proc/self/cgroup` (`0::<path>` for v2, `<id>:<controllers>:<path>` for v1).
+fn own_cgroup_dir(mount: &CgroupMount, own_cgroups: &str) -> Option<PathBuf> {
+    let path = own_cgroups.lines().find_map(|line| {
+        let (_, rest) = line.split_once(':')?;
+        let (controllers, path) = rest.split_once(':')?;
+        let matches = if mount.is_v2 {
+            controllers.is_empty()
+        } else {
+            controllers
+                .split(',')
+                .any(|controller| mount.controllers.iter().any(|c| c == controller))
+        };
+        matches.then_some(path)
+    })?;
+
+    let relative = match mount.root.as_str() {
+        "/" => path,
+        root => path.strip_prefix(root).unwrap_or(path),
+    };
+    Some(mount.mount_point.join(relative.trim_start_matches('/')))
+}
+
+fn read_number(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Value of a `name value` line of a stat file such as `memory.stat`.
+fn read_stat_value(path: &Path, name: &str) -> Option<u64> {
+    fs::read_to_string(path).ok()?.lines().find_map(|line| {
+        let (key, value) = line.split_once(' ')?;
+        (key == name).then(|| value.trim().parse().ok())?
+    })
+}
+
+/// Smallest limit set on the cgroup or one of its ancestors up to the mount point, limits of
+/// parents apply to their children.
+fn min_limit(dir: &Path, mount_point: &Path, read: impl Fn(&Path) -> Option<u64>) -> Option<u64> {
+    dir.ancestors()
+        .take_while(|ancestor| ancestor.starts_with(mount_point))
+        .filter_map(read)
+        .min()
+}
+
+/// Thousandths of a CPU allowed by the v1 `cpu.cfs_quota_us` and `cpu.cfs_period_us`, kept as an
+/// integer so limits compare as such. A quota of -1 is no quota.
+fn parse_cfs_quota(quota: &str, period: &str) -> Option<u64> {
+    let quota: i64 = quota.trim().parse().ok()?;
+    let period: u64 = period.trim().parse().ok()?;
+    (quota > 0 && period > 0).then(|| quota as u64 * 1000 / period)
+}
+
+/// Thousandths of a CPU allowed by the v2 `cpu.max`, `max <period>` is no quota.
+fn parse_cpu_max(content: &str) -> Option<u64> {
+    let (quota, period) = content.trim().split_once(' ')?;
+    let quota: u64 = quota.parse().ok()?;
+    let period: u64 = period.parse().ok()?;
+    (quota > 0 && period > 0).then(|| quota * 1000 / period)
+}
+
+/// Parses CPU lists such as `0-3,6`.
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((start, end)) => cpus.extend(start.parse::<usize>().ok()?..=end.parse().ok()?),
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    (!cpus.is_empty()).then_some(cpus)
+}
+
+/// Limits and usage of the cgroup reson runs in, from cgroup v2 or the v1 controllers.
+#[derive(Clone, Default)]
+pub struct CgroupLimits {
+    // Path of the cgroup, as listed in `/proc/self/cgroup`
+    pub path: String,
+    pub is_v2: bool,
+    // Bytes, None when unlimited
+    pub memory_limit: Option<u64>,
+    // Bytes in use without the inactive page cache the kernel reclaims before hitting the limit
+    pub memory_usage: Option<u64>,
+    // CPUs worth of time per period, None when unlimited
+    pub cpu_quota: Option<f64>,
+    // CPUs the cgroup may run on
+    pub cpuset: Option<Vec<usize>>,
+    // Total CPU time used by the cgroup, in microseconds
+    cpu_usage_usec: Option<u64>,
+}
+
+impl CgroupLimits {
+    /// None when reson's cgroup can't be found, for example on systems without cgroups.
+    pub fn read() -> Option<CgroupLimits> {
+        let own_cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+        Self::read_from(&read_mounts(), &own_cgroups)
+    }
+
+    /// Limits of the cgroups listed in `own_cgroups`, in the format of `/proc/self/cgroup`.
+    fn read_from(mounts: &[CgroupMount], own_cgroups: &str) -> Option<CgroupLimits> {
+        let v1_dir = |controller: &str| {
+            mounts
+                .iter()
+                .filter(|mount| !mount.is_v2)
+                .find(|mount| mount.controllers.iter().any(|c| c == controller))
+                .and_then(|mount| Some((own_cgroup_dir(mount, own_cgroups)?, mount)))
+        };
+
+        // v1 controllers take over from v2 on hybrid systems, where v2 has no controllers
+        if let Some((memory_dir, memory_mount)) = v1_dir("memory") {
+            let mut limits = CgroupLimits {
+                path: own_cgroups
+                    .lines()
+                    .find_map(|line| {
+                        let (_, rest) = line.split_once(':')?;
+                        let (controllers, path) = rest.split_once(':')?;
+                        controllers
+                            .split(',')
+                            .any(|c| c == "memory")
+                            .then_some(path)
+                    })
+                    .unwrap_or("/")
+                    .to_string(),
+                is_v2: false,
+                memory_limit: min_limit(&memory_dir, &memory_mount.mount_point, |dir| {
+                    read_number(&dir.join("memory.limit_in_bytes"))
+                })
+                .filter(|limit| *limit < V1_UNLIMITED),
+                memory_usage: read_number(&memory_dir.join("memory.usage_in_bytes")).map(|usage| {
+                    let inactive =
+                        read_stat_value(&memory_dir.join("memory.stat"), "total_inactive_file");
+                    usage.saturating_sub(inactive.unwrap_or(0))
+                }),
+                ..Default::default()
+            };
+
+            if let Some((cpu_dir, cpu_mount)) = v1_dir("cpu") {
+                let quota = |dir: &Path| {
+                    parse_cfs_quota(
+                        &fs::read_to_string(dir.join("cpu.cfs_quota_us")).ok()?,
+                        &fs::read_to_string(dir.join("cpu.cfs_period_us")).ok()?,
+                    )
+                };
+                limits.cpu_quota = min_limit(&cpu_dir, &cpu_mount.mount_point, quota)
+                    .map(|quota| quota as f64 / 1000.0);
+            }
+            if let Some((cpuset_dir, _)) = v1_dir("cpuset") {
+                limits.cpuset = ["cpuset.effective_cpus", "cpuset.cpus"]
+                    .iter()
+                    .find_map(|file| {
+                        parse_cpu_list(&fs::read_to_string(cpuset_dir.join(file)).ok()?)
+                    });
+            }
+            if let Some((cpuacct_dir, _)) = v1_dir("cpuacct") {
+                limits.cpu_usage_usec =
+                    read_number(&cpuacct_dir.join("cpuacct.usage")).map(|ns| ns / 1000);
+            }
+            return Some(limits);
+        }
+
+        let mount = mounts.iter().find(|mount| mount.is_v2)?;
+        let dir = own_cgroup_dir(mount, own_cgroups)?;
+        let cpu_max = |dir: &Path| parse_cpu_max(&fs::read_to_string(dir.join("cpu.max")).ok()?);
+
+        Some(CgroupLimits {
+            path: own_cgroups
+                .lines()
+                .find_map(|line| line.strip_prefix("0::"))
+                .unwrap_or("/")
+                .to_string(),
+            is_v2: true,
+            memory_limit: min_limit(&dir, &mount.mount_point, |dir| {
+                read_number(&dir.join("memory.max"))
+            }),
+            memory_usage: read_number(&dir.join("memory.current")).map(|usage| {
+                let inactive = read_stat_value(&dir.join("memory.stat"), "inactive_file");
+                usage.saturating_sub(inactive.unwrap_or(0))
+            }),
+            cpu_quota: min_limit(&dir, &mount.mount_point, cpu_max)
+                .map(|quota| quota as f64 / 1000.0),
+            cpuset: fs::read_to_string(dir.join("cpuset.cpus.effective"))
+                .ok()
+                .and_then(|list| parse_cpu_list(&list)),
+            cpu_usage_usec: read_stat_value(&dir.join("cpu.stat"), "usage_usec"),
+        })
+    }
+
+    /// CPUs the cgroup can use at most, the quota or the cpuset, whichever is smaller.
+    pub fn cpu_limit(&self, host_cpus: usize) -> f64 {
+        let cpuset = self.cpuset.as_ref().map_or(host_cpus, Vec::len);
+        self.cpu_quota
+            .unwrap_or(f64::MAX)
+            .min(cpuset.min(host_cpus) as f64)
+    }
+
+    /// Whether any limit is tighter than the host.
+    pub fn is_limited(&self, host_cpus: usize, host_memory: u64) -> bool {
+        self.memory_limit.is_some_and(|limit| limit < host_memory)
+            || self.cpu_limit(host_cpus) < host_cpus as f64
+    }
+}
+
+/// Limits of reson's own cgroup, read on every refresh, and the CPU the cgroup used since the
+/// previous one.
+#[derive(Default)]
+pub struct CgroupMonitor {
+    pub limits: Option<CgroupLimits>,
+    // CPUs worth of time used between the last two refreshes
+    pub cpu_usage: Option<f64>,
+    last_sample: Option<(Instant, u64)>,
+}
+
+impl CgroupMonitor {
+    pub fn refresh(&mut self) {
+        self.limits = CgroupLimits::read();
+
+        let now = Instant::now();
+        let usage = self
+            .limits
+            .as_ref()
+            .and_then(|limits| limits.cpu_usage_usec);
+        self.cpu_usage =
+            self.last_sample
+                .zip(usage)
+                .and_then(|((last_time, last_usage), usage)| {
+                    let seconds = now.duration_since(last_time).as_secs_f64();
+                    (seconds > 0.0)
+                        .then(|| usage.saturating_sub(last_usage) as f64 / 1_000_000.0 / seconds)
+                });
+        self.last_sample = usage.map(|usage| (now, usage));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `files` (path relative to the directory, contents) below a fresh temporary directory.
+    fn cgroup_tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("reson-cgroup-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        root
+    }
+
+    fn mount(mount_point: &Path, root: &str, is_v2: bool, controllers: &[&str]) -> CgroupMount {
+        CgroupMount {
+            mount_point: mount_point.to_path_buf(),
+            root: root.to_string(),
+            is_v2,
+            controllers: controllers.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parses_cpu_quotas() {
+        assert_eq!(parse_cfs_quota("50000\n", "100000\n"), Some(500));
+        assert_eq!(parse_cfs_quota("-1", "100000"), None);
+        assert_eq!(parse_cfs_quota("0", "100000"), None);
+        assert_eq!(parse_cfs_quota("50000", "0"), None);
+
+        assert_eq!(parse_cpu_max("200000 100000\n"), Some(2000));
+        assert_eq!(parse_cpu_max("max 100000\n"), None);
+        assert_eq!(parse_cpu_max("0 100000"), None);
+        assert_eq!(parse_cpu_max("50000 0"), None);
+    }
+
+    #[test]
+    fn parses_cgroup_mounts() {
+        let mounts = parse_mountinfo(
+            "22 1 0:21 / /proc rw,nosuid - proc proc rw\n\
+             25 24 0:22 / /sys/fs/cgroup/unified rw,nosuid shared:4 - cgroup2 cgroup2 rw,nsdelegate\n\
+             26 24 0:23 / /sys/fs/cgroup/cpu,cpuacct rw,nosuid shared:5 - cgroup cgroup rw,cpu,cpuacct\n\
+             612 611 0:27 /docker/0123abcd /sys/fs/cgroup ro,nosuid - cgroup2 cgroup2 rw\n",
+        );
+        assert_eq!(mounts.len(), 3);
+        assert!(mounts[0].is_v2);
+        assert_eq!(mounts[0].mount_point, Path::new("/sys/fs/cgroup/unified"));
+        assert!(!mounts[1].is_v2);
+        assert!(mounts[1].controllers.iter().any(|c| c == "cpuacct"));
+        assert_eq!(mounts[2].root, "/docker/0123abcd");
+    }
+
+    #[test]
+    fn resolves_own_cgroup_directory() {
+        let own_cgroups = "12:memory:/user.slice/user-0.slice\n\
+                           4:cpu,cpuacct:/user.slice\n\
+                           0::/docker/0123abcd/app\n";
+        let v1_cpu = mount(
+            Path::new("/sys/fs/cgroup/cpu,cpuacct"),
+            "/",
+            false,
+            &["rw", "cpu", "cpuacct"],
+        );
+        assert_eq!(
+            own_cgroup_dir(&v1_cpu, own_cgroups),
+            Some(PathBuf::from("/sys/fs/cgroup/cpu,cpuacct/user.slice"))
+        );
+        // Inside a container the mount starts at the container's own cgroup
+        let v2 = mount(
+            Path::new("/sys/fs/cgroup"),
+            "/docker/0123abcd",
+            true,
+            &["rw"],
+        );
+        assert_eq!(
+            own_cgroup_dir(&v2, own_cgroups),
+            Some(PathBuf::from("/sys/fs/cgroup/app"))
+        );
+        let v1_pids = mount(
+            Path::new("/sys/fs/cgroup/pids"),
+            "/",
+            false,
+            &["rw", "pids"],
+        );
+        assert_eq!(own_cgroup_dir(&v1_pids, own_cgroups), None);
+    }
+
+    #[test]
+    fn reads_v2_limits_of_nested_cgroups() {
+        let root = cgroup_tree(
+            "v2",
+            &[
+                ("parent/memory.max", "1073741824\n"),
+                ("parent/cpu.max", "max 100000\n"),
+                ("parent/child/memory.max", "max\n"),
+                ("parent/child/cpu.max", "50000 100000\n"),
+                ("parent/child/memory.current", "104857600\n"),
+                (
+                    "parent/child/memory.stat",
+                    "anon 1\ninactive_file 4857600\n",
+                ),
+                ("parent/child/cpuset.cpus.effective", "0-1,4\n"),
+                ("parent/child/cpu.stat", "usage_usec 2500\nuser_usec 2000\n"),
+            ],
+        );
+        let limits =
+            CgroupLimits::read_from(&[mount(&root, "/", true, &["rw"])], "0::/parent/child\n");
+        fs::remove_dir_all(&root).unwrap();
+
+        let limits = limits.unwrap();
+        assert!(limits.is_v2);
+        assert_eq!(limits.path, "/parent/child");
+        // The parent's memory limit applies to the unlimited child
+        assert_eq!(limits.memory_limit, Some(1073741824));
+        assert_eq!(limits.memory_usage, Some(100000000));
+        assert_eq!(limits.cpu_quota, Some(0.5));
+        assert_eq!(limits.cpuset, Some(vec![0, 1, 4]));
+        assert_eq!(limits.cpu_usage_usec, Some(2500));
+        assert_eq!(limits.cpu_limit(8), 0.5);
+    }
+
+    #[test]
+    fn reads_v1_limits_of_nested_cgroups() {
+        let root = cgroup_tree(
+            "v1",
+            &[
+                ("memory/memory.limit_in_bytes", "9223372036854771712\n"),
+                ("memory/app/memory.limit_in_bytes", "9223372036854771712\n"),
+                ("memory/app/memory.usage_in_bytes", "2048\n"),
+                ("memory/app/memory.stat", "total_inactive_file 1024\n"),
+                ("cpu/app/cpu.cfs_quota_us", "200000\n"),
+                ("cpu/app/cpu.cfs_period_us", "100000\n"),
+                ("cpu/app/job/cpu.cfs_quota_us", "-1\n"),
+                ("cpu/app/job/cpu.cfs_period_us", "100000\n"),
+            ],
+        );
+        let limits = CgroupLimits::read_from(
+            &[
+                mount(&root.join("unified"), "/", true, &["rw"]),
+                mount(&root.join("memory"), "/", false, &["rw", "memory"]),
+                mount(&root.join("cpu"), "/", false, &["rw", "cpu"]),
+            ],
+            "5:memory:/app\n3:cpu:/app/job\n0::/app\n",
+        );
+        fs::remove_dir_all(&root).unwrap();
+
+        let limits = limits.unwrap();
+        assert!(!limits.is_v2);
+        assert_eq!(limits.path, "/app");
+        // v1 reports no limit as a huge value
+        assert_eq!(limits.memory_limit, None);
+        assert_eq!(limits.memory_usage, Some(1024));
+        // The job has no quota of its own, its parent's applies
+        assert_eq!(limits.cpu_quota, Some(2.0));
+        assert_eq!(limits.cpuset, None);
+    }
+}
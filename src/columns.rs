@@ -0,0 +1,110 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::processes::ProcessColumn;
+
+/// Dialog to show, hide and reorder the processes table columns.
+pub struct ColumnPicker {
+    // Every column with its visibility, visible ones first in display order
+    entries: Vec<(ProcessColumn, bool)>,
+    cursor: usize,
+}
+
+pub enum ColumnPickerAction {
+    None,
+    Close,
+    Save(Vec<ProcessColumn>),
+}
+
+impl ColumnPicker {
+    pub fn new(visible: &[ProcessColumn]) -> Self {
+        let mut entries: Vec<_> = visible.iter().map(|column| (*column, true)).collect();
+        entries.extend(
+            ProcessColumn::ALL
+                .iter()
+                .filter(|column| !visible.contains(column))
+                .map(|column| (*column, false)),
+        );
+
+        Self { entries, cursor: 0 }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> ColumnPickerAction {
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.cursor = (self.cursor + 1).min(self.entries.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Char(' ') => self.entries[self.cursor].1 = !self.entries[self.cursor].1,
+            // Shifted j/k carry the column along
+            KeyCode::Char('J') if self.cursor + 1 < self.entries.len() => {
+                self.entries.swap(self.cursor, self.cursor + 1);
+                self.cursor += 1;
+            }
+            KeyCode::Char('K') if self.cursor > 0 => {
+                self.entries.swap(self.cursor, self.cursor - 1);
+                self.cursor -= 1;
+            }
+            KeyCode::Char('r') => *self = ColumnPicker::new(&ProcessColumn::DEFAULT),
+            KeyCode::Enter => {
+                let visible: Vec<ProcessColumn> = self
+                    .entries
+                    .iter()
+                    .filter(|(_, visible)| *visible)
+                    .map(|(column, _)| *column)
+                    .collect();
+                // An empty table is never what anyone wants
+                if !visible.is_empty() {
+                    return ColumnPickerAction::Save(visible);
+                }
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return ColumnPickerAction::Close,
+            _ => {}
+        }
+        ColumnPickerAction::None
+    }
+
+    /// Rows the dialog needs, the column list plus the help lines and the border.
+    pub fn height(&self) -> u16 {
+        (self.entries.len() + 5) as u16
+    }
+}
+
+pub fn create_column_picker(picker: &ColumnPicker) -> Paragraph<'_> {
+    let mut lines: Vec<Line> = picker
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, (column, visible))| {
+            let style = if i == picker.cursor {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else if *visible {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Line::styled(
+                format!(" [{}] {}", if *visible { "x" } else { " " }, column.title()),
+                style,
+            )
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(" j/k: Select | Space: Show/hide | J/K: Move"));
+    lines.push(Line::from(" r: Defaults | Enter: Save | Esc: Cancel"));
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .title("Process columns")
+            .borders(Borders::all())
+            .style(Style::default().fg(Color::Yellow)),
+    )
+}
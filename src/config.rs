@@ -0,0 +1,75 @@
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use color_eyre::{
+    eyre::{eyre, WrapErr},
+    Result,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::processes::ProcessColumn;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// How many seconds of memory and swap usage the history chart keeps.
+    pub memory_history_seconds: usize,
+    /// Named process queries, e.g. `busy = "cpu > 5"`, cycled with `v` in the processes tab.
+    pub views: BTreeMap<String, String>,
+    /// Columns of the processes table, in display order.
+    pub process_columns: Vec<ProcessColumn>,
+    /// Start with the memory gauge and CPU usage relative to reson's cgroup limits.
+    pub cgroup_limits: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            memory_history_seconds: 300,
+            views: BTreeMap::new(),
+            process_columns: ProcessColumn::DEFAULT.to_vec(),
+            cgroup_limits: false,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to defaults when it doesn't exist.
+    pub fn load() -> Result<Config> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content)
+                .wrap_err_with(|| format!("Invalid config file {}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => {
+                Err(err).wrap_err_with(|| format!("Failed to read config file {}", path.display()))
+            }
+        }
+    }
+
+    /// Writes the config file, creating its directory when needed. Returns where it was saved.
+    pub fn save(&self) -> Result<PathBuf> {
+        let path = config_path().ok_or_else(|| eyre!("No config directory found"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .wrap_err_with(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        fs::write(&path, toml::to_string(self)?)
+            .wrap_err_with(|| format!("Failed to write config file {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// `$XDG_CONFIG_HOME/reson/config.toml`, `~/.config/reson/config.toml` or `%APPDATA%\reson\config.toml`.
+pub fn config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+
+    Some(config_dir.join("reson").join("config.toml"))
+}
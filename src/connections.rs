@@ -0,0 +1,395 @@
+use std::{collections::HashMap, fs};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Constraint,
+    style::{Color, Modifier, Style},
+    widgets::{
+        block::{Position, Title},
+        Block, Borders, Row, Table,
+    },
+};
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+
+use crate::{
+    files::socket_inode,
+    layout::table_window,
+    sockets::{address_port, read_sockets, Socket},
+};
+
+/// A TCP or UDP socket and the process holding it.
+pub struct Connection {
+    pub socket: Socket,
+    // None when no readable fd points at the socket, such as TIME_WAIT sockets or sockets of
+    // other users' processes without root
+    pub pid: Option<Pid>,
+    pub command: String,
+}
+
+/// Maps socket inodes to the process having them open, by reading every `/proc/<pid>/fd`.
+/// A socket shared by several processes is attributed to the lowest PID.
+fn socket_owners() -> HashMap<u64, Pid> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return owners;
+    };
+
+    let mut pids: Vec<u32> = entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    pids.sort_unstable();
+
+    for pid in pids {
+        // Processes can exit while being read and other users' fds need root, both are skipped
+        let Ok(fds) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Ok(link) = fs::read_link(fd.path()) else {
+                continue;
+            };
+            if let Some(inode) = link.to_str().and_then(socket_inode) {
+                owners.entry(inode).or_insert(Pid::from_u32(pid));
+            }
+        }
+    }
+    owners
+}
+
+/// Reads the TCP and UDP sockets of reson's network namespace with their owners.
+pub fn read_connections(sys: &System) -> Vec<Connection> {
+    let owners = socket_owners();
+    let mut connections: Vec<Connection> = read_sockets("/proc/net")
+        .into_iter()
+        .filter(|socket| !socket.protocol.starts_with("unix"))
+        .map(|socket| {
+            let pid = owners.get(&socket.inode).copied();
+            let command = pid
+                .and_then(|pid| sys.process(pid))
+                .map_or(String::new(), |process| process.name().to_string());
+            Connection {
+                socket,
+                pid,
+                command,
+            }
+        })
+        .collect();
+
+    connections.sort_by(|a, b| {
+        a.socket
+            .protocol
+            .cmp(b.socket.protocol)
+            .then(address_port(&a.socket.local).cmp(&address_port(&b.socket.local)))
+            .then(a.socket.remote.cmp(&b.socket.remote))
+    });
+    connections
+}
+
+/// Filters on the text typed after `/`, a number matches the local or remote port and anything
+/// else the start of the state, case insensitively.
+fn matches_filter(connection: &Connection, filter: &str) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+    match filter.parse::<u16>() {
+        Ok(port) => {
+            address_port(&connection.socket.local) == Some(port)
+                || address_port(&connection.socket.remote) == Some(port)
+        }
+        Err(_) => connection
+            .socket
+            .state
+            .to_lowercase()
+            .starts_with(&filter.to_lowercase()),
+    }
+}
+
+/// TCP and UDP connections of the system, refreshed with the rest of the data while open.
+pub struct ConnectionsView {
+    connections: Vec<Connection>,
+    filter: String,
+    editing_filter: bool,
+    selected: usize,
+    scroll: usize,
+}
+
+pub enum ConnectionsViewAction {
+    None,
+    Close,
+    ShowProcess(Pid),
+}
+
+impl ConnectionsView {
+    pub fn new(sys: &System) -> Self {
+        Self {
+            connections: read_connections(sys),
+            filter: String::new(),
+            editing_filter: false,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn refresh(&mut self, sys: &System) {
+        // The cursor stays on the same socket when the list changes around it
+        let selected_inode = self.visible().get(self.selected).map(|c| c.socket.inode);
+        self.connections = read_connections(sys);
+        if let Some(index) = selected_inode.and_then(|inode| {
+            self.visible()
+                .iter()
+                .position(|connection| connection.socket.inode == inode)
+        }) {
+            self.selected = index;
+        }
+    }
+
+    fn visible(&self) -> Vec<&Connection> {
+        self.connections
+            .iter()
+            .filter(|connection| matches_filter(connection, &self.filter))
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> ConnectionsViewAction {
+        if self.editing_filter {
+            match code {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Enter => self.editing_filter = false,
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.editing_filter = false;
+                }
+                _ => {}
+            }
+            self.selected = 0;
+            return ConnectionsViewAction::None;
+        }
+
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => self.selected = self.selected.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::PageDown => self.selected = self.selected.saturating_add(10),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(10),
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Enter => {
+                if let Some(pid) = self.visible().get(self.selected).and_then(|c| c.pid) {
+                    return ConnectionsViewAction::ShowProcess(pid);
+                }
+            }
+            // An active filter is cleared before Esc closes the view
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.selected = 0;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => return ConnectionsViewAction::Close,
+            _ => {}
+        }
+        ConnectionsViewAction::None
+    }
+}
+
+/// Number of connections per state, the most common first.
+fn state_summary(connections: &[Connection]) -> String {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for connection in connections {
+        // UDP sockets without a peer have no state
+        let state = match connection.socket.state {
+            "" => "UNCONN",
+            state => state,
+        };
+        match counts.iter_mut().find(|(name, _)| *name == state) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((state, 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    counts
+        .iter()
+        .map(|(state, count)| format!("{} {}", state, count))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+pub struct ConnectionsTable<'a> {
+    pub chart: Table<'a>,
+    pub widths: Vec<Constraint>,
+    pub max_scroll: usize,
+    pub scroll_position: usize,
+}
+
+pub fn create_connections_table<'a>(
+    view: &mut ConnectionsView,
+    width: usize,
+    height: usize,
+) -> ConnectionsTable<'a> {
+    let connections: Vec<&Connection> = view
+        .connections
+        .iter()
+        .filter(|connection| matches_filter(connection, &view.filter))
+        .collect();
+    view.selected = view.selected.min(connections.len().saturating_sub(1));
+
+    let window = table_window(connections.len(), height, view.scroll, Some(view.selected));
+    view.scroll = window.scroll;
+
+    let header = Row::new(vec![
+        "Proto", "Local", "Remote", "State", "Send-Q", "Recv-Q", "PID", "Command",
+    ])
+    .style(Style::default().fg(Color::Gray));
+    let rows: Vec<Row> = connections
+        .iter()
+        .enumerate()
+        .skip(view.scroll)
+        .take(window.visible_lines)
+        .map(|(index, connection)| {
+            let socket = &connection.socket;
+            let row = Row::new(vec![
+                socket.protocol.to_string(),
+                socket.local.clone(),
+                socket.remote.clone(),
+                socket.state.to_string(),
+                socket.tx_queue.to_string(),
+                socket.rx_queue.to_string(),
+                connection
+                    .pid
+                    .map_or("-".to_string(), |pid| pid.to_string()),
+                connection.command.clone(),
+            ]);
+            if index == view.selected {
+                row.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let filter = if view.editing_filter {
+        format!(" | Filter: {}▏", view.filter)
+    } else if !view.filter.is_empty() {
+        format!(" | Filter: {}", view.filter)
+    } else {
+        String::new()
+    };
+    let title = format!(
+        "Connections: {}/{}{} | /: Filter by state or port | Enter: Show process | Esc: Close",
+        connections.len(),
+        view.connections.len(),
+        filter
+    );
+    let summary = state_summary(&view.connections);
+
+    // -2 for the border, -1 for the scrollbar, plus the spacing between columns
+    let fixed = [6, 0, 0, 11, 7, 7, 8, 16];
+    let address_width = width.saturating_sub(3 + 7 + fixed.iter().sum::<usize>()) / 2;
+    let widths = fixed
+        .iter()
+        .map(|width| match width {
+            0 => Constraint::Length(address_width as u16),
+            width => Constraint::Length(*width as u16),
+        })
+        .collect();
+
+    ConnectionsTable {
+        chart: Table::new(rows)
+            .header(header)
+            .block(
+                Block::default()
+                    .title(title)
+                    .title(Title::from(summary).position(Position::Bottom))
+                    .borders(Borders::all())
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .column_spacing(1),
+        widths,
+        max_scroll: window.max_scroll,
+        scroll_position: view.scroll,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(local: &str, remote: &str, state: &'static str, pid: Option<u32>) -> Connection {
+        Connection {
+            socket: Socket {
+                protocol: "tcp",
+                local: local.to_string(),
+                remote: remote.to_string(),
+                state,
+                tx_queue: 0,
+                rx_queue: 0,
+                inode: 0,
+            },
+            pid: pid.map(Pid::from_u32),
+            command: String::new(),
+        }
+    }
+
+    fn view(connections: Vec<Connection>) -> ConnectionsView {
+        ConnectionsView {
+            connections,
+            filter: String::new(),
+            editing_filter: false,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    #[test]
+    fn filters_numbers_by_port_and_text_by_state() {
+        let listening = connection("0.0.0.0:22", "0.0.0.0:0", "LISTEN", None);
+        let established = connection("10.0.0.2:51234", "10.0.0.1:443", "ESTABLISHED", None);
+
+        assert!(matches_filter(&listening, ""));
+        assert!(matches_filter(&listening, "22"));
+        assert!(!matches_filter(&established, "22"));
+        assert!(matches_filter(&established, "443"));
+        assert!(matches_filter(&established, "51234"));
+        // Part of a port doesn't match
+        assert!(!matches_filter(&established, "44"));
+
+        assert!(matches_filter(&listening, "listen"));
+        assert!(matches_filter(&established, "EST"));
+        assert!(!matches_filter(&established, "LISTEN"));
+        // States match by prefix only
+        assert!(!matches_filter(&established, "LISHED"));
+        // Too large for a port, so it's compared to the state
+        assert!(!matches_filter(&listening, "70000"));
+    }
+
+    #[test]
+    fn enter_shows_the_process_of_the_selected_connection() {
+        let mut view = view(vec![
+            connection("0.0.0.0:22", "0.0.0.0:0", "LISTEN", Some(100)),
+            connection("0.0.0.0:80", "0.0.0.0:0", "LISTEN", Some(200)),
+            connection("10.0.0.2:51234", "10.0.0.1:443", "TIME_WAIT", None),
+        ]);
+
+        view.handle_key(KeyCode::Down);
+        assert!(matches!(
+            view.handle_key(KeyCode::Enter),
+            ConnectionsViewAction::ShowProcess(pid) if pid == Pid::from_u32(200)
+        ));
+
+        // Without an owner there is no process to jump to
+        view.handle_key(KeyCode::Down);
+        assert!(matches!(
+            view.handle_key(KeyCode::Enter),
+            ConnectionsViewAction::None
+        ));
+
+        // The selection indexes the filtered list
+        for key in [KeyCode::Char('/'), KeyCode::Char('2'), KeyCode::Char('2')] {
+            view.handle_key(key);
+        }
+        view.handle_key(KeyCode::Enter);
+        assert!(matches!(
+            view.handle_key(KeyCode::Enter),
+            ConnectionsViewAction::ShowProcess(pid) if pid == Pid::from_u32(100)
+        ));
+    }
+}
@@ -0,0 +1,547 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    sync::{Mutex, OnceLock},
+};
+
+// Length of the short container IDs, as shown by `docker ps`
+const SHORT_ID_LENGTH: usize = 12;
+// Pod UIDs are shortened to their first group
+const SHORT_POD_LENGTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkloadKind {
+    Docker,
+    Podman,
+    Containerd,
+    Crio,
+    Lxc,
+    // A container ID without a recognisable runtime
+    Container,
+    // A systemd service or scope
+    Unit,
+}
+
+impl WorkloadKind {
+    fn name(self) -> &'static str {
+        match self {
+            WorkloadKind::Docker => "docker",
+            WorkloadKind::Podman => "podman",
+            WorkloadKind::Containerd => "containerd",
+            WorkloadKind::Crio => "crio",
+            WorkloadKind::Lxc => "lxc",
+            WorkloadKind::Container => "container",
+            WorkloadKind::Unit => "unit",
+        }
+    }
+}
+
+/// What a process runs in, derived from the path of its cgroup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workload {
+    pub kind: WorkloadKind,
+    // Full container ID, the LXC container name or the systemd unit name
+    pub id: String,
+    // UID of the Kubernetes pod the container is in
+    pub pod: Option<String>,
+}
+
+impl Workload {
+    /// Recognises the cgroup layouts of Docker, Podman, containerd, CRI-O and LXC with both the
+    /// cgroupfs and systemd drivers, and Kubernetes pods. Other processes get the innermost
+    /// systemd unit, None when they're in no unit such as kernel threads.
+    pub fn from_cgroup_path(path: &str) -> Option<Workload> {
+        let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+        let pod = components.iter().find_map(|component| pod_uid(component));
+
+        // Nested cgroups such as Podman's `libpod-<id>.scope/container`, the innermost wins
+        for (index, component) in components.iter().enumerate().rev() {
+            let parent = index.checked_sub(1).map(|index| components[index]);
+            if let Some((kind, id)) = container_id(component, parent) {
+                return Some(Workload { kind, id, pod });
+            }
+        }
+
+        components
+            .iter()
+            .rev()
+            .find(|component| component.ends_with(".service") || component.ends_with(".scope"))
+            .map(|unit| Workload {
+                kind: WorkloadKind::Unit,
+                id: unit.to_string(),
+                pod: None,
+            })
+    }
+
+    /// Short text shown in the table and matched by queries: the unit name, or the runtime with
+    /// the container name when it can be looked up locally or else the short ID.
+    pub fn label(&self) -> String {
+        match self.kind {
+            WorkloadKind::Unit => self.id.clone(),
+            WorkloadKind::Lxc => format!("lxc:{}", self.id),
+            kind => {
+                let container = container_name(kind, &self.id)
+                    .unwrap_or_else(|| shorten(&self.id, SHORT_ID_LENGTH).to_string());
+                match &self.pod {
+                    Some(pod) => format!("pod:{}/{}", shorten(pod, SHORT_POD_LENGTH), container),
+                    None => format!("{}:{}", kind.name(), container),
+                }
+            }
+        }
+    }
+}
+
+/// Label of the workload the cgroup belongs to, `-` when it's neither a container nor a unit.
+pub fn cgroup_label(path: &str) -> String {
+    Workload::from_cgroup_path(path).map_or("-".to_string(), |workload| workload.label())
+}
+
+fn shorten(id: &str, length: usize) -> &str {
+    id.get(..length).unwrap_or(id)
+}
+
+fn is_container_id(text: &str) -> bool {
+    text.len() == 64 && text.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+/// Pod UID from `pod<uid>` (cgroupfs) or `kubepods-<class>-pod<uid>.slice` (systemd, which
+/// writes the dashes of the UID as underscores).
+fn pod_uid(component: &str) -> Option<String> {
+    let uid = match component.strip_suffix(".slice") {
+        Some(slice) => slice.rsplit_once("-pod")?.1,
+        None => component.strip_prefix("pod")?,
+    };
+    (uid.len() == 36).then(|| uid.replace('_', "-"))
+}
+
+fn container_id(component: &str, parent: Option<&str>) -> Option<(WorkloadKind, String)> {
+    if let Some(name) = component.strip_prefix("lxc.payload.") {
+        return Some((WorkloadKind::Lxc, name.to_string()));
+    }
+    if matches!(parent, Some("lxc" | "lxc.payload")) {
+        return Some((WorkloadKind::Lxc, component.to_string()));
+    }
+
+    // systemd driver: `<runtime>-<id>.scope`, conmon monitors count as their container
+    if let Some(scope) = component.strip_suffix(".scope") {
+        for (prefix, kind) in [
+            ("docker-", WorkloadKind::Docker),
+            ("libpod-conmon-", WorkloadKind::Podman),
+            ("libpod-", WorkloadKind::Podman),
+            ("cri-containerd-", WorkloadKind::Containerd),
+            ("crio-conmon-", WorkloadKind::Crio),
+            ("crio-", WorkloadKind::Crio),
+        ] {
+            if let Some(id) = scope.strip_prefix(prefix).filter(|id| is_container_id(id)) {
+                return Some((kind, id.to_string()));
+            }
+        }
+        return None;
+    }
+
+    // cgroupfs driver: the bare ID under a directory named after the runtime
+    if !is_container_id(component) {
+        return None;
+    }
+    let kind = match parent {
+        Some("docker") => WorkloadKind::Docker,
+        Some("libpod_parent") => WorkloadKind::Podman,
+        _ => WorkloadKind::Container,
+    };
+    Some((kind, component.to_string()))
+}
+
+/// Names of containers by ID, looked up once. Queries and the export run without the app's
+/// state, so the cache is shared by the whole process.
+fn container_names() -> &'static Mutex<HashMap<String, Option<String>>> {
+    static NAMES: OnceLock<Mutex<HashMap<String, Option<String>>>> = OnceLock::new();
+    NAMES.get_or_init(Default::default)
+}
+
+fn container_name(kind: WorkloadKind, id: &str) -> Option<String> {
+    let mut names = container_names()
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    names
+        .entry(id.to_string())
+        .or_insert_with(|| match kind {
+            WorkloadKind::Docker => read_docker_name(id),
+            WorkloadKind::Podman => read_podman_name(id),
+            _ => None,
+        })
+        .clone()
+}
+
+/// Name from the Docker daemon's state directory, readable by root.
+fn read_docker_name(id: &str) -> Option<String> {
+    let config =
+        fs::read_to_string(format!("/var/lib/docker/containers/{}/config.v2.json", id)).ok()?;
+    docker_name(&config)
+}
+
+/// Docker stores the name with a leading slash.
+fn docker_name(config: &str) -> Option<String> {
+    let name = json_fields(config)
+        .into_iter()
+        .find_map(|(key, value)| (key == "Name").then(|| json_string(value)))??;
+    Some(name.trim_start_matches('/').to_string())
+}
+
+/// Name from the container list of rootful Podman, or of rootless Podman for the current user.
+fn read_podman_name(id: &str) -> Option<String> {
+    let mut paths =
+        vec!["/var/lib/containers/storage/overlay-containers/containers.json".to_string()];
+    if let Ok(home) = env::var("HOME") {
+        paths.push(format!(
+            "{}/.local/share/containers/storage/overlay-containers/containers.json",
+            home
+        ));
+    }
+
+    paths
+        .iter()
+        .find_map(|path| podman_name(&fs::read_to_string(path).ok()?, id))
+}
+
+/// First name of the container with the ID in a `containers.json` list.
+fn podman_name(containers: &str, id: &str) -> Option<String> {
+    json_objects(containers).into_iter().find_map(|container| {
+        let fields = json_fields(container);
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| *value)
+        };
+        if json_string(field("id")?)? != id {
+            return None;
+        }
+        json_string(field("names")?)
+    })
+}
+
+// The container lists are read with a minimal JSON scanner. It only tells strings from structure,
+// which is all that's needed to pick string fields out of the runtimes' own files.
+
+/// Length of the string literal at the start of `text`, including its quotes.
+fn json_string_length(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Calls `visit` with the depth and position of every `{`, `}`, `[`, `]`, `:` and `,` outside of
+/// strings. Nested values have a depth above 1.
+fn scan_json(text: &str, mut visit: impl FnMut(usize, usize, char)) {
+    let mut depth = 0;
+    let mut index = 0;
+    while let Some(c) = text[index..].chars().next() {
+        match c {
+            '"' => {
+                let Some(length) = json_string_length(&text[index..]) else {
+                    return;
+                };
+                index += length;
+                continue;
+            }
+            '{' | '[' => {
+                depth += 1;
+                visit(depth, index, c);
+            }
+            '}' | ']' => {
+                visit(depth, index, c);
+                depth = depth.saturating_sub(1);
+            }
+            ':' | ',' => visit(depth, index, c),
+            _ => {}
+        }
+        index += c.len_utf8();
+    }
+}
+
+/// The objects of a JSON array of objects.
+fn json_objects(array: &str) -> Vec<&str> {
+    let mut objects = Vec::new();
+    let mut start = None;
+    scan_json(array, |depth, index, c| match (depth, c) {
+        (2, '{') => start = Some(index),
+        (2, '}') => {
+            if let Some(start) = start.take() {
+                objects.push(&array[start..=index]);
+            }
+        }
+        _ => {}
+    });
+    objects
+}
+
+/// Keys of the outermost object with the unparsed text of their values.
+fn json_fields(object: &str) -> Vec<(String, &str)> {
+    let mut fields = Vec::new();
+    let mut key_start = None;
+    let mut value_start = None;
+    let mut push_field = |key_start: usize, value_start: usize, end: usize| {
+        if let Some(key) = json_string(&object[key_start..value_start - 1]) {
+            fields.push((key, object[value_start..end].trim()));
+        }
+    };
+    scan_json(object, |depth, index, c| match (depth, c) {
+        (1, '{') => key_start = Some(index + 1),
+        (1, ':') => value_start = Some(index + 1),
+        (1, ',' | '}') => {
+            if let (Some(key), Some(value)) = (key_start, value_start.take()) {
+                push_field(key, value, index);
+            }
+            key_start = Some(index + 1);
+        }
+        _ => {}
+    });
+    fields
+}
+
+/// The string value, or the first string of an array such as Podman's names.
+fn json_string(value: &str) -> Option<String> {
+    let value = value.trim();
+    let value = match value.strip_prefix('[') {
+        Some(array) => array.trim_start(),
+        None => value,
+    };
+    if !value.starts_with('"') {
+        return None;
+    }
+    let literal = &value[1..json_string_length(value)? - 1];
+
+    let mut text = String::new();
+    let mut chars = literal.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => text.push('\n'),
+            't' => text.push('\t'),
+            'r' => text.push('\r'),
+            'b' => text.push('\u{8}'),
+            'f' => text.push('\u{c}'),
+            'u' => {
+                let code: String = chars.by_ref().take(4).collect();
+                text.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            escaped => text.push(escaped),
+        }
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f4c2b1a0e9d8c7b6a5f4e3d2c1b0a9f8e7d6c5b4a3f2e1d0c9b8a7f6e5d4c3b";
+    const POD: &str = "0a1b2c3d-4e5f-6789-abcd-ef0123456789";
+    const POD_SYSTEMD: &str = "0a1b2c3d_4e5f_6789_abcd_ef0123456789";
+
+    /// Workload of the cgroup v2 line of `/proc/<pid>/cgroup`.
+    fn workload(line: &str) -> Option<(WorkloadKind, String, Option<String>)> {
+        let path = line.strip_prefix("0::").unwrap();
+        Workload::from_cgroup_path(path).map(|workload| (workload.kind, workload.id, workload.pod))
+    }
+
+    #[test]
+    fn recognises_container_runtimes() {
+        let cases = [
+            // Docker, cgroupfs and systemd drivers
+            (format!("0::/docker/{}", ID), WorkloadKind::Docker),
+            (
+                format!("0::/system.slice/docker-{}.scope", ID),
+                WorkloadKind::Docker,
+            ),
+            // Podman, rootful cgroupfs, rootless systemd with its nested cgroup, and conmon
+            (format!("0::/libpod_parent/{}", ID), WorkloadKind::Podman),
+            (
+                format!(
+                    "0::/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container",
+                    ID
+                ),
+                WorkloadKind::Podman,
+            ),
+            (
+                format!("0::/machine.slice/libpod-conmon-{}.scope", ID),
+                WorkloadKind::Podman,
+            ),
+            // containerd and CRI-O outside of Kubernetes
+            (
+                format!("0::/system.slice/cri-containerd-{}.scope", ID),
+                WorkloadKind::Containerd,
+            ),
+            (
+                format!("0::/machine.slice/crio-{}.scope", ID),
+                WorkloadKind::Crio,
+            ),
+            (
+                format!("0::/machine.slice/crio-conmon-{}.scope", ID),
+                WorkloadKind::Crio,
+            ),
+            // A bare ID under an unknown parent
+            (format!("0::/ecs/task/{}", ID), WorkloadKind::Container),
+        ];
+        for (line, kind) in cases {
+            assert_eq!(
+                workload(&line),
+                Some((kind, ID.to_string(), None)),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn recognises_kubernetes_pods() {
+        let cases = [
+            // cgroupfs driver, besteffort and burstable QoS, and guaranteed with no QoS level
+            (
+                format!("0::/kubepods/besteffort/pod{}/{}", POD, ID),
+                WorkloadKind::Container,
+            ),
+            (
+                format!("0::/kubepods/burstable/pod{}/{}", POD, ID),
+                WorkloadKind::Container,
+            ),
+            (
+                format!("0::/kubepods/pod{}/{}", POD, ID),
+                WorkloadKind::Container,
+            ),
+            // systemd driver, which writes the dashes of the UID as underscores
+            (
+                format!(
+                    "0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod{}.slice/cri-containerd-{}.scope",
+                    POD_SYSTEMD, ID
+                ),
+                WorkloadKind::Containerd,
+            ),
+            (
+                format!(
+                    "0::/kubepods.slice/kubepods-besteffort.slice/kubepods-besteffort-pod{}.slice/crio-{}.scope",
+                    POD_SYSTEMD, ID
+                ),
+                WorkloadKind::Crio,
+            ),
+            (
+                format!(
+                    "0::/kubepods.slice/kubepods-pod{}.slice/crio-conmon-{}.scope",
+                    POD_SYSTEMD, ID
+                ),
+                WorkloadKind::Crio,
+            ),
+        ];
+        for (line, kind) in cases {
+            assert_eq!(
+                workload(&line),
+                Some((kind, ID.to_string(), Some(POD.to_string()))),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn recognises_lxc_containers_and_units() {
+        let cases = [
+            ("0::/lxc.payload.web01", WorkloadKind::Lxc, "web01"),
+            (
+                "0::/lxc.payload.web01/system.slice/nginx.service",
+                WorkloadKind::Lxc,
+                "web01",
+            ),
+            ("0::/lxc/web01", WorkloadKind::Lxc, "web01"),
+            ("0::/lxc.payload/web01", WorkloadKind::Lxc, "web01"),
+            (
+                "0::/system.slice/nginx.service",
+                WorkloadKind::Unit,
+                "nginx.service",
+            ),
+            (
+                "0::/user.slice/user-1000.slice/session-2.scope",
+                WorkloadKind::Unit,
+                "session-2.scope",
+            ),
+            ("0::/init.scope", WorkloadKind::Unit, "init.scope"),
+        ];
+        for (line, kind, id) in cases {
+            assert_eq!(
+                workload(line),
+                Some((kind, id.to_string(), None)),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn falls_back_to_a_dash() {
+        for path in [
+            "/",
+            "/user.slice",
+            "/kubepods/besteffort",
+            "/docker/not-an-id",
+        ] {
+            assert_eq!(Workload::from_cgroup_path(path), None, "{}", path);
+            assert_eq!(cgroup_label(path), "-");
+        }
+    }
+
+    #[test]
+    fn labels_workloads() {
+        assert_eq!(cgroup_label("/system.slice/nginx.service"), "nginx.service");
+        assert_eq!(cgroup_label("/lxc.payload.web01"), "lxc:web01");
+        // Without the runtime's state the short ID stands in for the name
+        assert_eq!(
+            cgroup_label(&format!("/system.slice/cri-containerd-{}.scope", ID)),
+            format!("containerd:{}", &ID[..12])
+        );
+        assert_eq!(
+            cgroup_label(&format!("/kubepods/burstable/pod{}/{}", POD, ID)),
+            format!("pod:0a1b2c3d/{}", &ID[..12])
+        );
+    }
+
+    #[test]
+    fn reads_docker_names() {
+        let config = r#"{"StreamConfig":{},"State":{"Running":true},"ID":"3f4c","Config":{"Hostname":"3f4c","Labels":{"Name":"label"}},"Name":"/web","Driver":"overlay2"}"#;
+        assert_eq!(docker_name(config).as_deref(), Some("web"));
+        // Keys in another order, spacing and escapes in other strings
+        let config = r#"{ "Path": "sh", "Args": ["-c", "echo \"{\" , \"Name\":\"/wrong\""], "Name" : "/db" }"#;
+        assert_eq!(docker_name(config).as_deref(), Some("db"));
+        assert_eq!(docker_name(r#"{"ID":"3f4c"}"#), None);
+    }
+
+    #[test]
+    fn reads_podman_names() {
+        let containers = format!(
+            r#"[{{"id":"{other}","names":["other"],"image":"x"}},{{"names":["web", "alias"],"metadata":"{{\"image-name\":\"x\",\"id\":\"{id}\"}}","id":"{id}"}}]"#,
+            other = "0".repeat(64),
+            id = ID
+        );
+        assert_eq!(podman_name(&containers, ID).as_deref(), Some("web"));
+        assert_eq!(podman_name(&containers, &"1".repeat(64)), None);
+    }
+
+    #[test]
+    fn unescapes_json_strings() {
+        assert_eq!(json_string(r#""a\"b\\cé""#).as_deref(), Some("a\"b\\cé"));
+        assert_eq!(
+            json_string(r#"[ "first", "second" ]"#).as_deref(),
+            Some("first")
+        );
+        assert_eq!(json_string("42"), None);
+        assert_eq!(json_string(r#""unterminated"#), None);
+    }
+}
@@ -5,7 +5,7 @@ use ratatui::{
 };
 use sysinfo::{CpuExt, System, SystemExt};
 
-use crate::layout::get_highlight_style;
+use crate::{cgroup::CgroupMonitor, layout::get_highlight_style};
 
 pub struct CpuBarchart<'a_> {
     pub chart: BarChart<'a_>,
@@ -13,21 +13,31 @@ pub struct CpuBarchart<'a_> {
     pub real_content_length: usize,
 }
 
-pub fn create_cpu_barchart(
-    sys: &System,
+/// Per-CPU usage bars. With `cgroup` only the CPUs of the cgroup's cpuset are shown and the
+/// total is the cgroup's usage relative to its CPU limit.
+pub fn create_cpu_barchart<'a>(
+    sys: &'a System,
+    cgroup: Option<&CgroupMonitor>,
     layout_width: usize,
     scroll_position: usize,
     is_selected: bool,
-) -> CpuBarchart<'_> {
+) -> CpuBarchart<'a> {
     let bar_width: u16 = 7;
     let bar_gap: u16 = 2;
     let visible_bars = layout_width / (bar_width + bar_gap) as usize;
     let highlight_style = get_highlight_style(is_selected);
 
-    let cpu_data: Vec<Bar> = sys
+    let limits = cgroup.and_then(|cgroup| cgroup.limits.as_ref());
+    let cpuset = limits.and_then(|limits| limits.cpuset.as_ref());
+    let cpus: Vec<_> = sys
         .cpus()
         .iter()
         .enumerate()
+        .filter(|(index, _)| cpuset.is_none_or(|cpuset| cpuset.contains(index)))
+        .collect();
+
+    let cpu_data: Vec<Bar> = cpus
+        .iter()
         .skip(scroll_position)
         .take(visible_bars)
         .map(|(cpu_count, cpu)| {
@@ -40,8 +50,8 @@ pub fn create_cpu_barchart(
         })
         .collect();
 
-    let all_bar_count = sys.cpus().len();
-    let max_scroll = all_bar_count.saturating_sub(visible_bars as usize);
+    let all_bar_count = cpus.len();
+    let max_scroll = all_bar_count.saturating_sub(visible_bars);
     let real_content_length = if visible_bars == all_bar_count {
         0
     } else {
@@ -51,11 +61,27 @@ pub fn create_cpu_barchart(
     let barchart = BarChart::default()
         .block(
             Block::default()
-                .title(format!(
-                    "CPU Usage, Total: {}%, Max Frequency: {} MHz",
-                    sys.global_cpu_info().cpu_usage().round(),
-                    sys.global_cpu_info().frequency()
-                ))
+                .title(match (cgroup, limits) {
+                    (Some(cgroup), Some(limits)) => {
+                        let limit = limits.cpu_limit(sys.cpus().len());
+                        let used = cgroup.cpu_usage.unwrap_or(0.0);
+                        // A host reporting no CPUs would otherwise print NaN
+                        let percent = if limit > 0.0 {
+                            format!("{}%", (used / limit * 100.0).round())
+                        } else {
+                            "-".to_string()
+                        };
+                        format!(
+                            "CPU Usage of cgroup {}, {:.2} of {:.2} CPUs: {}",
+                            limits.path, used, limit, percent
+                        )
+                    }
+                    _ => format!(
+                        "CPU Usage, Total: {}%, Max Frequency: {} MHz",
+                        sys.global_cpu_info().cpu_usage().round(),
+                        sys.global_cpu_info().frequency()
+                    ),
+                })
                 .title_style(highlight_style.title)
                 .borders(Borders::all())
                 .border_style(highlight_style.border)
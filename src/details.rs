@@ -0,0 +1,397 @@
+use std::fs;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use sysinfo::{Pid, ProcessExt, System, SystemExt, UserExt};
+
+use crate::procstat::ProcStat;
+use crate::query::state_name;
+use crate::smaps::{read_mapping_breakdown, MappingUsage, SmapsRollup};
+
+/// Soft and hard value of a `/proc/<pid>/limits` entry.
+pub struct ResourceLimit {
+    pub name: String,
+    pub soft: String,
+    pub hard: String,
+    pub units: String,
+}
+
+/// Snapshot of everything the detail view shows about a process.
+pub struct ProcessDetails {
+    pub name: String,
+    pub pid: Pid,
+    pub parent: Option<Pid>,
+    pub user: String,
+    pub cmd: String,
+    pub exe: String,
+    pub cwd: String,
+    pub environ: Vec<String>,
+    pub status: &'static str,
+    pub threads: Option<u64>,
+    pub fd_count: Option<usize>,
+    pub limits: Vec<ResourceLimit>,
+    pub cgroups: Vec<String>,
+    pub start_time: u64,
+    // Seconds spent in user and kernel mode since the process started
+    pub cpu_time: Option<f64>,
+    pub rss: u64,
+    pub vsz: u64,
+    pub shared: Option<u64>,
+    pub smaps: Option<SmapsRollup>,
+    pub mappings: Vec<MappingUsage>,
+}
+
+impl ProcessDetails {
+    /// Reads the process, returns None once it's gone.
+    pub fn read(sys: &System, pid: Pid) -> Option<ProcessDetails> {
+        let process = sys.process(pid)?;
+        let proc_dir = format!("/proc/{}", pid);
+
+        Some(ProcessDetails {
+            name: process.name().to_string(),
+            pid,
+            parent: process.parent(),
+            user: process
+                .user_id()
+                .and_then(|id| sys.get_user_by_id(id))
+                .map(|user| user.name().to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            cmd: process.cmd().join(" "),
+            exe: process.exe().display().to_string(),
+            cwd: process.cwd().display().to_string(),
+            environ: process.environ().to_vec(),
+            status: state_name(process.status()),
+            threads: read_status_field(&proc_dir, "Threads:"),
+            fd_count: fs::read_dir(format!("{}/fd", proc_dir))
+                .ok()
+                .map(|entries| entries.count()),
+            limits: read_limits(&proc_dir),
+            cgroups: fs::read_to_string(format!("{}/cgroup", proc_dir))
+                .map(|content| content.lines().map(str::to_string).collect())
+                .unwrap_or_default(),
+            start_time: process.start_time(),
+            cpu_time: ProcStat::read(pid).map(|stat| stat.cpu_time()),
+            rss: process.memory(),
+            vsz: process.virtual_memory(),
+            shared: read_shared_memory(&proc_dir),
+            smaps: SmapsRollup::read(pid),
+            mappings: read_mapping_breakdown(pid),
+        })
+    }
+}
+
+fn read_status_field(proc_dir: &str, field: &str) -> Option<u64> {
+    let status = fs::read_to_string(format!("{}/status", proc_dir)).ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+fn read_limits(proc_dir: &str) -> Vec<ResourceLimit> {
+    let Ok(content) = fs::read_to_string(format!("{}/limits", proc_dir)) else {
+        return Vec::new();
+    };
+
+    // Names contain spaces, so split on the runs of spaces the kernel pads the columns with
+    content
+        .lines()
+        .skip(1) // header
+        .filter_map(|line| {
+            let mut columns = line
+                .split("  ")
+                .map(str::trim)
+                .filter(|column| !column.is_empty());
+            Some(ResourceLimit {
+                name: columns.next()?.to_string(),
+                soft: columns.next()?.to_string(),
+                hard: columns.next()?.to_string(),
+                units: columns.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Resident shared pages from `/proc/<pid>/statm`, in bytes.
+fn read_shared_memory(proc_dir: &str) -> Option<u64> {
+    let statm = fs::read_to_string(format!("{}/statm", proc_dir)).ok()?;
+    let shared_pages: u64 = statm.split_whitespace().nth(2)?.parse().ok()?;
+    Some(shared_pages * page_size())
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // SAFETY: sysconf has no memory safety requirements
+    match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as u64,
+        _ => 4096,
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+/// Formats seconds since the Unix epoch as local time, `YYYY-MM-DD HH:MM:SS`.
+#[cfg(unix)]
+pub fn format_timestamp(timestamp: u64) -> String {
+    let time = timestamp as libc::time_t;
+    // SAFETY: tm is plain data, localtime_r fills it and doesn't keep the pointers
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return timestamp.to_string();
+    }
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+/// Formats seconds since the Unix epoch as UTC, `YYYY-MM-DD HH:MM:SS UTC`.
+#[cfg(not(unix))]
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Days to civil date, from Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:02}",
+        total / 3600,
+        total / 60 % 60,
+        total % 60,
+        ((seconds - total as f64) * 100.0) as u64
+    )
+}
+
+/// Sizes of mappings span from a few KB to GBs, so the unit follows the size.
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 * 1024 {
+        format!("{:.1} GB", bytes as f64 / 1024.0 / 1024.0 / 1024.0)
+    } else if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / 1024.0 / 1024.0)
+    } else {
+        format!("{} KB", bytes / 1024)
+    }
+}
+
+/// Detail pane of one process, refreshed with the rest of the data while open.
+pub struct DetailsView {
+    pid: Pid,
+    scroll: usize,
+    details: Option<ProcessDetails>,
+}
+
+pub enum DetailsViewAction {
+    None,
+    Close,
+}
+
+impl DetailsView {
+    pub fn new(sys: &System, pid: Pid) -> Self {
+        Self {
+            pid,
+            scroll: 0,
+            details: ProcessDetails::read(sys, pid),
+        }
+    }
+
+    pub fn refresh(&mut self, sys: &System) {
+        self.details = ProcessDetails::read(sys, self.pid);
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> DetailsViewAction {
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return DetailsViewAction::Close,
+            _ => {}
+        }
+        DetailsViewAction::None
+    }
+}
+
+pub struct DetailsWidget<'a> {
+    pub chart: Paragraph<'a>,
+    pub max_scroll: usize,
+    pub scroll_position: usize,
+}
+
+pub fn create_details_widget<'a>(
+    view: &mut DetailsView,
+    width: usize,
+    height: usize,
+) -> DetailsWidget<'a> {
+    let block = Block::default()
+        .borders(Borders::all())
+        .style(Style::default().fg(Color::Yellow));
+
+    let Some(details) = &view.details else {
+        return DetailsWidget {
+            chart: Paragraph::new(format!("Process {} has exited", view.pid))
+                .block(block.title(format!("Process {}", view.pid))),
+            max_scroll: 0,
+            scroll_position: 0,
+        };
+    };
+
+    let heading = |text: &str| {
+        Line::styled(
+            text.to_string(),
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )
+    };
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", name), Style::default().fg(Color::Gray)),
+            Span::styled(value, Style::default().fg(Color::White)),
+        ])
+    };
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "n/a".to_string());
+    let mb = |bytes: u64| format!("{} MB", bytes / 1024 / 1024);
+
+    let mut lines = vec![
+        field("Command line", details.cmd.clone()),
+        field("Executable", details.exe.clone()),
+        field("Working dir", details.cwd.clone()),
+        field("User", details.user.clone()),
+        field(
+            "Parent",
+            details
+                .parent
+                .map_or("-".to_string(), |parent| parent.to_string()),
+        ),
+        field("Status", details.status.to_string()),
+        field(
+            "Threads",
+            optional(details.threads.map(|threads| threads.to_string())),
+        ),
+        field(
+            "Open files",
+            optional(details.fd_count.map(|count| count.to_string())),
+        ),
+        field("Started", format_timestamp(details.start_time)),
+        field("CPU time", optional(details.cpu_time.map(format_duration))),
+        field("RSS", mb(details.rss)),
+        field("VSZ", mb(details.vsz)),
+        field("Shared", optional(details.shared.map(mb))),
+        field("PSS", optional(details.smaps.map(|smaps| mb(smaps.pss)))),
+        field("USS", optional(details.smaps.map(|smaps| mb(smaps.uss)))),
+        field("Swap", optional(details.smaps.map(|smaps| mb(smaps.swap)))),
+        Line::from(""),
+        heading(&format!(
+            "{:<14}{:>10}{:>12}{:>12}{:>12}{:>12}",
+            "Mappings", "Count", "Size", "RSS", "PSS", "Swap"
+        )),
+    ];
+
+    if details.mappings.is_empty() {
+        lines.push(Line::from("n/a"));
+    }
+    lines.extend(details.mappings.iter().map(|mapping| {
+        Line::from(format!(
+            "{:<14}{:>10}{:>12}{:>12}{:>12}{:>12}",
+            mapping.kind.name(),
+            mapping.count,
+            format_size(mapping.size),
+            format_size(mapping.rss),
+            format_size(mapping.pss),
+            format_size(mapping.swap)
+        ))
+    }));
+
+    lines.push(Line::from(""));
+    lines.push(heading("Cgroups"));
+
+    if details.cgroups.is_empty() {
+        lines.push(Line::from("n/a"));
+    }
+    lines.extend(
+        details
+            .cgroups
+            .iter()
+            .map(|cgroup| Line::from(cgroup.clone())),
+    );
+
+    lines.push(Line::from(""));
+    lines.push(heading(&format!(
+        "{:<26}{:<22}{:<22}{}",
+        "Limits", "Soft", "Hard", "Units"
+    )));
+    if details.limits.is_empty() {
+        lines.push(Line::from("n/a"));
+    }
+    lines.extend(details.limits.iter().map(|limit| {
+        Line::from(format!(
+            "{:<26}{:<22}{:<22}{}",
+            limit.name, limit.soft, limit.hard, limit.units
+        ))
+    }));
+
+    lines.push(Line::from(""));
+    lines.push(heading("Environment"));
+    if details.environ.is_empty() {
+        lines.push(Line::from("n/a"));
+    }
+    lines.extend(details.environ.iter().map(|var| Line::from(var.clone())));
+
+    // -2 for the border, long lines wrap so they count once per row they take
+    let visible_lines = height.saturating_sub(2);
+    let inner_width = width.saturating_sub(2).max(1);
+    let wrapped_lines: usize = lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(inner_width))
+        .sum();
+    let max_scroll = wrapped_lines.saturating_sub(visible_lines);
+    view.scroll = view.scroll.min(max_scroll);
+
+    DetailsWidget {
+        chart: Paragraph::new(lines)
+            .block(block.title(format!(
+                "{} ({}) | j/k: Scroll | Esc: Close",
+                details.name, details.pid
+            )))
+            .wrap(Wrap { trim: false })
+            .scroll((view.scroll as u16, 0)),
+        max_scroll,
+        scroll_position: view.scroll,
+    }
+}
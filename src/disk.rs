@@ -16,7 +16,7 @@ pub fn create_disks_widget(
     layout_height: usize,
     scroll_position: usize,
     is_selected: bool,
-) -> DisksWidget {
+) -> DisksWidget<'_> {
     // -2 for border
     let visible_lines = layout_height - 2;
     let highlight_style = get_highlight_style(is_selected);
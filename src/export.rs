@@ -0,0 +1,120 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::{eyre::eyre, Result};
+use sysinfo::{Pid, PidExt, Process, ProcessExt, System, SystemExt};
+
+use crate::{
+    processes::UserNames,
+    procstat::ProcStatCache,
+    query::{state_name, Query},
+    vmstat::VmStatSampler,
+};
+
+const HEADER: [&str; 8] = [
+    "user", "pid", "ppid", "cpu", "mem_mb", "time", "state", "command",
+];
+
+/// Writes the processes matching `query` as CSV, ordered by PID, and returns how many were
+/// written. Uses the same query language as the processes table filter.
+pub fn export_processes(sys: &System, query: Option<&str>, out: &mut impl Write) -> Result<usize> {
+    let query = query
+        .map(Query::parse)
+        .transpose()
+        .map_err(|err| eyre!("Invalid query: {}", err))?;
+    let users = UserNames::new(sys);
+    let mut stats = ProcStatCache::default();
+    if query.as_ref().is_some_and(|query| query.needs_cgroup()) {
+        stats.refresh_cgroups(sys);
+    }
+
+    let mut processes: Vec<_> = sys
+        .processes()
+        .values()
+        .filter(|process| {
+            query
+                .as_ref()
+                .is_none_or(|query| query.matches(process, &users, &stats))
+        })
+        .collect();
+    processes.sort_by_key(|process| process.pid());
+
+    write_csv(&processes, &users, out)?;
+    Ok(processes.len())
+}
+
+/// Writes the processes that are still running as CSV to `reson-<timestamp>.csv` in the current
+/// directory, returns the file name and how many were written.
+pub fn export_processes_to_file(
+    sys: &System,
+    users: &UserNames,
+    pids: &[Pid],
+) -> Result<(String, usize)> {
+    let mut processes: Vec<&Process> = pids.iter().filter_map(|pid| sys.process(*pid)).collect();
+    processes.sort_by_key(|process| process.pid());
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = format!("reson-{}.csv", timestamp);
+    let mut out = BufWriter::new(File::create(&path)?);
+    write_csv(&processes, users, &mut out)?;
+    out.flush()?;
+    Ok((path, processes.len()))
+}
+
+/// Samples `/proc/vmstat` over `interval` and writes the paging and fault rates as CSV, one
+/// `metric,value` row per rate.
+pub fn export_vmstat(interval: Duration, out: &mut impl Write) -> Result<()> {
+    let mut sampler = VmStatSampler::new();
+    sampler.refresh();
+    thread::sleep(interval);
+    sampler.refresh();
+    let rates = sampler
+        .rates
+        .ok_or_else(|| eyre!("/proc/vmstat is not available"))?;
+
+    writeln!(out, "metric,value")?;
+    for (name, value) in rates.fields() {
+        writeln!(out, "{},{:.1}", name, value)?;
+    }
+    Ok(())
+}
+
+fn write_csv(processes: &[&Process], users: &UserNames, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "{}", HEADER.join(","))?;
+    for process in processes {
+        let command = if process.cmd().is_empty() {
+            process.name().to_string()
+        } else {
+            process.cmd().join(" ")
+        };
+        let fields = [
+            users.name(process).to_string(),
+            process.pid().to_string(),
+            process
+                .parent()
+                .map_or(String::new(), |ppid| ppid.as_u32().to_string()),
+            format!("{:.1}", process.cpu_usage()),
+            (process.memory() / 1024 / 1024).to_string(),
+            process.run_time().to_string(),
+            state_name(process.status()).to_string(),
+            command,
+        ];
+
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+/// Quotes a field when it contains characters that would break the CSV row.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,271 @@
+use std::fs;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Constraint,
+    style::{Color, Style},
+    widgets::{Block, Borders, Row, Table},
+};
+use sysinfo::Pid;
+
+use crate::{layout::table_window, sockets::sockets_by_inode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Directory,
+    Socket,
+    Pipe,
+    AnonInode,
+    Device,
+    Other,
+}
+
+impl FileKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            FileKind::File => "file",
+            FileKind::Directory => "dir",
+            FileKind::Socket => "socket",
+            FileKind::Pipe => "pipe",
+            FileKind::AnonInode => "anon_inode",
+            FileKind::Device => "device",
+            FileKind::Other => "other",
+        }
+    }
+}
+
+/// One entry of `/proc/<pid>/fd`.
+pub struct OpenFile {
+    pub fd: u32,
+    pub kind: FileKind,
+    // Protocol of sockets, empty for everything else
+    pub protocol: &'static str,
+    // Path for files, addresses and state for sockets
+    pub target: String,
+}
+
+/// Reads the open files of the process, None if its fd directory can't be read, which needs
+/// the same user or root.
+pub fn read_open_files(pid: Pid) -> Option<Vec<OpenFile>> {
+    let fd_dir = format!("/proc/{}/fd", pid);
+    let entries = fs::read_dir(&fd_dir).ok()?;
+    // The socket tables of the network namespace the process is in
+    let sockets = sockets_by_inode(&format!("/proc/{}/net", pid));
+
+    let mut files: Vec<OpenFile> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let fd: u32 = entry.file_name().to_str()?.parse().ok()?;
+            // The fd can be closed between listing and reading the link
+            let link = fs::read_link(entry.path()).ok()?;
+            let link = link.to_string_lossy();
+
+            let kind = file_kind(&link);
+            let file = if kind == FileKind::Socket {
+                match socket_inode(&link).and_then(|inode| sockets.get(&inode)) {
+                    Some(socket) => {
+                        let mut target = socket.local.clone();
+                        if !socket.remote.is_empty() {
+                            target = format!("{} -> {}", target, socket.remote);
+                        }
+                        if !socket.state.is_empty() {
+                            target = format!("{} ({})", target, socket.state);
+                        }
+                        OpenFile {
+                            fd,
+                            kind: FileKind::Socket,
+                            protocol: socket.protocol,
+                            target,
+                        }
+                    }
+                    // Netlink, packet and other sockets aren't in the tables read
+                    None => OpenFile {
+                        fd,
+                        kind: FileKind::Socket,
+                        protocol: "",
+                        target: link.to_string(),
+                    },
+                }
+            } else {
+                OpenFile {
+                    fd,
+                    kind,
+                    protocol: "",
+                    target: link.to_string(),
+                }
+            };
+            Some(file)
+        })
+        .collect();
+
+    files.sort_by_key(|file| file.fd);
+    Some(files)
+}
+
+/// Kind of file an fd link points at. Sockets, pipes and anonymous inodes aren't paths but
+/// `socket:[inode]`, `pipe:[inode]` and `anon_inode:name`.
+fn file_kind(link: &str) -> FileKind {
+    if link.starts_with("socket:") {
+        FileKind::Socket
+    } else if link.starts_with("pipe:") {
+        FileKind::Pipe
+    } else if link.starts_with("anon_inode:") {
+        FileKind::AnonInode
+    } else if link.starts_with("/dev/") {
+        FileKind::Device
+    } else if link.starts_with('/') {
+        match fs::metadata(link) {
+            Ok(metadata) if metadata.is_dir() => FileKind::Directory,
+            _ => FileKind::File,
+        }
+    } else {
+        FileKind::Other
+    }
+}
+
+/// Inode of a `socket:[inode]` fd link, the key of the socket tables in `/proc/net`.
+pub fn socket_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+/// Open files of one process, refreshed with the rest of the data while open.
+pub struct OpenFilesView {
+    pid: Pid,
+    name: String,
+    scroll: usize,
+    // None when the fd directory can't be read
+    files: Option<Vec<OpenFile>>,
+}
+
+pub enum OpenFilesViewAction {
+    None,
+    Close,
+}
+
+impl OpenFilesView {
+    pub fn new(pid: Pid, name: String) -> Self {
+        Self {
+            pid,
+            name,
+            scroll: 0,
+            files: read_open_files(pid),
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.files = read_open_files(self.pid);
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> OpenFilesViewAction {
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('o') => {
+                return OpenFilesViewAction::Close
+            }
+            _ => {}
+        }
+        OpenFilesViewAction::None
+    }
+}
+
+pub struct OpenFilesTable<'a> {
+    pub chart: Table<'a>,
+    pub widths: Vec<Constraint>,
+    pub max_scroll: usize,
+    pub scroll_position: usize,
+}
+
+pub fn create_open_files_table<'a>(
+    view: &mut OpenFilesView,
+    width: usize,
+    height: usize,
+) -> OpenFilesTable<'a> {
+    let files = view.files.as_deref().unwrap_or_default();
+    let window = table_window(files.len(), height, view.scroll, None);
+    view.scroll = window.scroll;
+
+    let header =
+        Row::new(vec!["FD", "Type", "Protocol", "Target"]).style(Style::default().fg(Color::Gray));
+    let rows: Vec<Row> = files
+        .iter()
+        .skip(view.scroll)
+        .take(window.visible_lines)
+        .map(|file| {
+            Row::new(vec![
+                file.fd.to_string(),
+                file.kind.name().to_string(),
+                file.protocol.to_string(),
+                file.target.clone(),
+            ])
+        })
+        .collect();
+
+    let title = match &view.files {
+        Some(files) => format!(
+            "Open files of {} ({}): {} | j/k: Scroll | Esc: Close",
+            view.name,
+            view.pid,
+            files.len()
+        ),
+        None => format!(
+            "Open files of {} ({}) | Not readable, the process is gone or owned by another user",
+            view.name, view.pid
+        ),
+    };
+
+    // -2 for the border, -1 for the scrollbar, plus the spacing between columns
+    let fixed = [6, 10, 14];
+    let target_width = width.saturating_sub(3 + 3 + fixed.iter().sum::<usize>());
+    let widths = fixed
+        .iter()
+        .chain([target_width].iter())
+        .map(|width| Constraint::Length(*width as u16))
+        .collect();
+
+    OpenFilesTable {
+        chart: Table::new(rows)
+            .header(header)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::all())
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .column_spacing(1),
+        widths,
+        max_scroll: window.max_scroll,
+        scroll_position: view.scroll,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_fd_links() {
+        assert_eq!(file_kind("socket:[52813]"), FileKind::Socket);
+        assert_eq!(file_kind("pipe:[52811]"), FileKind::Pipe);
+        assert_eq!(file_kind("anon_inode:[eventfd]"), FileKind::AnonInode);
+        assert_eq!(file_kind("anon_inode:inotify"), FileKind::AnonInode);
+        assert_eq!(file_kind("/dev/null"), FileKind::Device);
+        assert_eq!(file_kind("/"), FileKind::Directory);
+        assert_eq!(file_kind("/proc/self/status"), FileKind::File);
+        assert_eq!(file_kind("net:[4026531840]"), FileKind::Other);
+    }
+
+    #[test]
+    fn reads_socket_inodes() {
+        assert_eq!(socket_inode("socket:[52813]"), Some(52813));
+        assert_eq!(socket_inode("pipe:[52811]"), None);
+        assert_eq!(socket_inode("socket:[52813"), None);
+        assert_eq!(socket_inode("socket:[]"), None);
+    }
+}
@@ -0,0 +1,148 @@
+use regex::{Regex, RegexBuilder};
+use sysinfo::{Process, ProcessExt};
+
+use crate::{processes::UserNames, procstat::ProcStatCache, query::Query};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterMode {
+    Substring,
+    Regex,
+    Query,
+}
+
+/// Incremental filter of the processes table. Text modes match the command name, the full
+/// command line, the user and the PID, case insensitively. The query mode takes expressions like
+/// `cpu > 5 && user == "postgres"`.
+pub struct ProcessFilter {
+    pub query: String,
+    pub mode: FilterMode,
+    // Set while the query is being typed, keys go to the filter instead of the app
+    pub editing: bool,
+    // Name of the configured view the query came from, cleared once it's edited
+    pub view: Option<String>,
+    regex: Option<Regex>,
+    expression: Option<Query>,
+    pub error: Option<String>,
+}
+
+impl ProcessFilter {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            mode: FilterMode::Substring,
+            editing: false,
+            view: None,
+            regex: None,
+            expression: None,
+            error: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.query.push(c);
+        self.view = None;
+        self.compile();
+    }
+
+    pub fn pop(&mut self) {
+        self.query.pop();
+        self.view = None;
+        self.compile();
+    }
+
+    pub fn clear(&mut self) {
+        self.query.clear();
+        self.editing = false;
+        self.view = None;
+        self.compile();
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            FilterMode::Substring => FilterMode::Regex,
+            FilterMode::Regex => FilterMode::Query,
+            FilterMode::Query => FilterMode::Substring,
+        };
+        self.view = None;
+        self.compile();
+    }
+
+    /// Replaces the filter with a named query from the config.
+    pub fn apply_view(&mut self, name: &str, query: &str) {
+        self.query = query.to_string();
+        self.mode = FilterMode::Query;
+        self.view = Some(name.to_string());
+        self.compile();
+    }
+
+    /// Whether the query needs the cgroups of the processes to be read.
+    pub fn needs_cgroup(&self) -> bool {
+        self.mode == FilterMode::Query
+            && self
+                .expression
+                .as_ref()
+                .is_some_and(|expression| expression.needs_cgroup())
+    }
+
+    fn compile(&mut self) {
+        self.regex = None;
+        self.expression = None;
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+
+        match self.mode {
+            FilterMode::Substring => {}
+            FilterMode::Regex => match RegexBuilder::new(&self.query)
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(regex) => self.regex = Some(regex),
+                Err(_) => self.error = Some("invalid regex".to_string()),
+            },
+            FilterMode::Query => match Query::parse(&self.query) {
+                Ok(expression) => self.expression = Some(expression),
+                Err(err) => self.error = Some(err.to_string()),
+            },
+        }
+    }
+
+    pub fn matches(&self, process: &Process, users: &UserNames, stats: &ProcStatCache) -> bool {
+        if !self.is_active() {
+            return true;
+        }
+
+        // Invalid patterns and queries match nothing until they're fixed
+        match self.mode {
+            FilterMode::Substring => {
+                let query = self.query.to_lowercase();
+                text_fields(process, users)
+                    .iter()
+                    .any(|field| field.to_lowercase().contains(&query))
+            }
+            FilterMode::Regex => self.regex.as_ref().is_some_and(|regex| {
+                text_fields(process, users)
+                    .iter()
+                    .any(|field| regex.is_match(field))
+            }),
+            FilterMode::Query => self
+                .expression
+                .as_ref()
+                .is_some_and(|expression| expression.matches(process, users, stats)),
+        }
+    }
+}
+
+fn text_fields(process: &Process, users: &UserNames) -> [String; 4] {
+    [
+        process.name().to_string(),
+        process.cmd().join(" "),
+        users.name(process).to_string(),
+        process.pid().to_string(),
+    ]
+}
@@ -16,10 +16,8 @@ impl AppLayout {
             main_layout: MainLayout {
                 cpu_plus_memory_layout: CpuMemoryLayout {
                     cpu_layout: Rect::default(),
-                    memory_layout: MemoryLayout {
-                        ram_layout: Rect::default(),
-                        swap_layout: Rect::default(),
-                    },
+                    memory_layout: MemoryLayout::empty(),
+                    pressure_layout: PressureLayout::empty(),
                 },
                 processes_layout: Rect::default(),
                 disk_layout: Rect::default(),
@@ -42,15 +40,93 @@ pub struct MainLayout {
 pub struct CpuMemoryLayout {
     pub cpu_layout: Rect,
     pub memory_layout: MemoryLayout,
+    pub pressure_layout: PressureLayout,
 }
 
 #[derive(Clone)]
 pub struct MemoryLayout {
+    pub area: Rect,
     pub ram_layout: Rect,
-    pub swap_layout: Rect,
+    pub swap_layout: SwapLayout,
+    pub breakdown_layout: Rect,
+    // Only present in the zoomed memory view
+    pub history_layout: Option<Rect>,
 }
 
-pub fn prepare_layout(f: &mut ratatui::Frame<'_>) -> AppLayout {
+impl MemoryLayout {
+    fn empty() -> MemoryLayout {
+        MemoryLayout {
+            area: Rect::default(),
+            ram_layout: Rect::default(),
+            swap_layout: SwapLayout::empty(),
+            breakdown_layout: Rect::default(),
+            history_layout: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SwapLayout {
+    pub area: Rect,
+    pub gauge: Rect,
+    pub devices: Rect,
+}
+
+impl SwapLayout {
+    fn empty() -> SwapLayout {
+        SwapLayout {
+            area: Rect::default(),
+            gauge: Rect::default(),
+            devices: Rect::default(),
+        }
+    }
+}
+
+/// Panel expanded over the whole main area.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ZoomedPanel {
+    Cpu,
+    Memory,
+    Processes,
+    Disks,
+    Networks,
+}
+
+#[derive(Clone)]
+pub struct PressureLayout {
+    pub area: Rect,
+    pub cpu: PressureRowLayout,
+    pub memory: PressureRowLayout,
+    pub io: PressureRowLayout,
+}
+
+impl PressureLayout {
+    fn empty() -> PressureLayout {
+        PressureLayout {
+            area: Rect::default(),
+            cpu: PressureRowLayout::empty(),
+            memory: PressureRowLayout::empty(),
+            io: PressureRowLayout::empty(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PressureRowLayout {
+    pub summary: Rect,
+    pub sparkline: Rect,
+}
+
+impl PressureRowLayout {
+    fn empty() -> PressureRowLayout {
+        PressureRowLayout {
+            summary: Rect::default(),
+            sparkline: Rect::default(),
+        }
+    }
+}
+
+pub fn prepare_layout(f: &mut ratatui::Frame<'_>, zoomed: Option<ZoomedPanel>) -> AppLayout {
     use Constraint::{Length, Min};
     let app_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -60,12 +136,29 @@ pub fn prepare_layout(f: &mut ratatui::Frame<'_>) -> AppLayout {
     let main_area = app_layout[0];
     let footer_area = app_layout[1];
 
+    let main_layout = match zoomed {
+        Some(panel) => prepare_zoomed_layout(main_area, panel),
+        None => prepare_main_layout(main_area),
+    };
+
     AppLayout {
-        main_layout: prepare_main_layout(main_area),
+        main_layout,
         footer_area,
     }
 }
 
+/// Area of the given size centered in `area`, shrunk to fit when `area` is too small.
+pub fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
 pub fn is_within_rect(pos: (u16, u16), rect: &Rect) -> bool {
     let (x, y) = pos;
     x >= rect.x && x < rect.x + rect.width && y >= rect.y && y < rect.y + rect.height
@@ -85,7 +178,11 @@ fn prepare_main_layout(inner_area: Rect) -> MainLayout {
 
     let cpu_plus_memory_layout = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints([
+            Constraint::Percentage(40), // CPU
+            Constraint::Percentage(30), // Memory
+            Constraint::Percentage(30), // Pressure
+        ])
         .split(main_layout[0]);
 
     let memory_layout = Layout::default()
@@ -97,9 +194,13 @@ fn prepare_main_layout(inner_area: Rect) -> MainLayout {
         cpu_plus_memory_layout: CpuMemoryLayout {
             cpu_layout: cpu_plus_memory_layout[0],
             memory_layout: MemoryLayout {
+                area: cpu_plus_memory_layout[1],
                 ram_layout: memory_layout[0],
-                swap_layout: memory_layout[1],
+                swap_layout: prepare_swap_layout(memory_layout[1]),
+                breakdown_layout: cpu_plus_memory_layout[1],
+                history_layout: None,
             },
+            pressure_layout: prepare_pressure_layout(cpu_plus_memory_layout[2]),
         },
         processes_layout: main_layout[1],
         disk_layout: main_layout[2],
@@ -107,6 +208,108 @@ fn prepare_main_layout(inner_area: Rect) -> MainLayout {
     }
 }
 
+fn prepare_zoomed_layout(inner_area: Rect, panel: ZoomedPanel) -> MainLayout {
+    let area = Layout::default()
+        .direction(Direction::Vertical)
+        .vertical_margin(1)
+        .constraints([Constraint::Min(0)])
+        .split(inner_area)[0];
+
+    // Everything but the zoomed panel gets an empty area and is skipped when rendering
+    let mut main_layout = MainLayout {
+        cpu_plus_memory_layout: CpuMemoryLayout {
+            cpu_layout: Rect::default(),
+            memory_layout: MemoryLayout::empty(),
+            pressure_layout: PressureLayout::empty(),
+        },
+        processes_layout: Rect::default(),
+        disk_layout: Rect::default(),
+        network_layout: Rect::default(),
+    };
+
+    match panel {
+        ZoomedPanel::Cpu => main_layout.cpu_plus_memory_layout.cpu_layout = area,
+        ZoomedPanel::Memory => {
+            main_layout.cpu_plus_memory_layout.memory_layout = prepare_zoomed_memory_layout(area)
+        }
+        ZoomedPanel::Processes => main_layout.processes_layout = area,
+        ZoomedPanel::Disks => main_layout.disk_layout = area,
+        ZoomedPanel::Networks => main_layout.network_layout = area,
+    }
+
+    main_layout
+}
+
+fn prepare_zoomed_memory_layout(area: Rect) -> MemoryLayout {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(6), Constraint::Min(0)])
+        .split(area);
+
+    let gauges = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+
+    let details = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[1]);
+
+    MemoryLayout {
+        area,
+        ram_layout: gauges[0],
+        swap_layout: prepare_swap_layout(gauges[1]),
+        breakdown_layout: details[0],
+        history_layout: Some(details[1]),
+    }
+}
+
+fn prepare_swap_layout(area: Rect) -> SwapLayout {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1) // border
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+
+    SwapLayout {
+        area,
+        gauge: rows[0],
+        devices: rows[1],
+    }
+}
+
+fn prepare_pressure_layout(area: Rect) -> PressureLayout {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1) // border
+        .constraints([
+            Constraint::Ratio(1, 3), // CPU
+            Constraint::Ratio(1, 3), // Memory
+            Constraint::Ratio(1, 3), // IO
+        ])
+        .split(area);
+
+    PressureLayout {
+        area,
+        cpu: prepare_pressure_row_layout(rows[0]),
+        memory: prepare_pressure_row_layout(rows[1]),
+        io: prepare_pressure_row_layout(rows[2]),
+    }
+}
+
+fn prepare_pressure_row_layout(area: Rect) -> PressureRowLayout {
+    let row = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(area);
+
+    PressureRowLayout {
+        summary: row[0],
+        sparkline: row[1],
+    }
+}
+
 pub struct HighlightStyle {
     pub border: Style,
     pub title: Style,
@@ -150,3 +353,59 @@ pub fn get_horizontal_scrollbar<'a>() -> Scrollbar<'a> {
 pub fn get_vertical_scrollbar<'a>() -> Scrollbar<'a> {
     Scrollbar::new(ScrollbarOrientation::VerticalRight)
 }
+
+/// Rows of a bordered table with a header that are on screen, and how far it's scrolled.
+pub struct TableWindow {
+    pub visible_lines: usize,
+    pub max_scroll: usize,
+    pub scroll: usize,
+}
+
+/// Fits `row_count` rows into a bordered table with a header that is `height` high. The scroll
+/// position moves just enough to keep `selected` on screen and never goes past the last row.
+pub fn table_window(
+    row_count: usize,
+    height: usize,
+    scroll: usize,
+    selected: Option<usize>,
+) -> TableWindow {
+    // -2 for the border, -1 for the header
+    let visible_lines = height.saturating_sub(3).max(1);
+    let max_scroll = row_count.saturating_sub(visible_lines);
+    let scroll = match selected {
+        Some(selected) if selected < scroll => selected,
+        Some(selected) if selected >= scroll + visible_lines => selected + 1 - visible_lines,
+        _ => scroll,
+    };
+    TableWindow {
+        visible_lines,
+        max_scroll,
+        scroll: scroll.min(max_scroll),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_the_table_scroll_to_the_last_row() {
+        let window = table_window(30, 13, 50, None);
+        assert_eq!(window.visible_lines, 10);
+        assert_eq!(window.max_scroll, 20);
+        assert_eq!(window.scroll, 20);
+
+        let window = table_window(5, 13, 3, None);
+        assert_eq!(window.max_scroll, 0);
+        assert_eq!(window.scroll, 0);
+    }
+
+    #[test]
+    fn scrolls_the_table_to_the_selected_row() {
+        assert_eq!(table_window(30, 13, 0, Some(4)).scroll, 0);
+        assert_eq!(table_window(30, 13, 0, Some(12)).scroll, 3);
+        assert_eq!(table_window(30, 13, 8, Some(2)).scroll, 2);
+        // A table too low for its header still shows the selected row
+        assert_eq!(table_window(30, 2, 0, Some(7)).scroll, 7);
+    }
+}
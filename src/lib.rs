@@ -1,9 +1,30 @@
 mod app;
+mod cgroup;
+mod columns;
+mod config;
+mod connections;
+mod container;
 mod cpu;
+mod details;
 mod disk;
+mod export;
+mod files;
+mod filter;
 mod layout;
 mod memory;
 mod network;
+mod pressure;
 mod processes;
+mod procstat;
+mod query;
+mod scheduling;
+mod signals;
+mod smaps;
+mod sockets;
+mod swap;
+mod threads;
+mod vmstat;
 
 pub use app::App;
+pub use config::Config;
+pub use export::{export_processes, export_vmstat};
@@ -1,18 +1,94 @@
-use color_eyre::{eyre::Ok, Result};
+use std::time::Duration;
+
+use color_eyre::{
+    eyre::{bail, eyre, Ok},
+    Result,
+};
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
 use ratatui::{prelude::CrosstermBackend, Terminal};
-use reson::App;
+use reson::{export_processes, export_vmstat, App, Config};
 use sysinfo::{System, SystemExt};
 
+const USAGE: &str = "Usage: reson [--export [--query QUERY | --view NAME] | --vmstat]";
+
+#[derive(Default)]
+struct Args {
+    // Print the process list as CSV and exit instead of starting the TUI
+    export: bool,
+    query: Option<String>,
+    view: Option<String>,
+    // Print the paging and fault rates from /proc/vmstat as CSV and exit
+    vmstat: bool,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args::default();
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--export" => args.export = true,
+            "--vmstat" => args.vmstat = true,
+            "--query" => {
+                args.query = Some(iter.next().ok_or_else(|| eyre!("--query needs a value"))?)
+            }
+            "--view" => args.view = Some(iter.next().ok_or_else(|| eyre!("--view needs a value"))?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => bail!("Unknown argument '{}'\n{}", arg, USAGE),
+        }
+    }
+
+    if !args.export && (args.query.is_some() || args.view.is_some()) {
+        bail!("--query and --view only apply to --export\n{}", USAGE);
+    }
+    if args.export && args.vmstat {
+        bail!("Use either --export or --vmstat\n{}", USAGE);
+    }
+    Ok(args)
+}
+
+fn export(config: &Config, args: Args, sys: &mut System) -> Result<()> {
+    let query = match (args.query, args.view) {
+        (Some(_), Some(_)) => bail!("Use either --query or --view"),
+        (Some(query), None) => Some(query),
+        (None, Some(view)) => Some(
+            config
+                .views
+                .get(&view)
+                .cloned()
+                .ok_or_else(|| eyre!("No view named '{}' in the config", view))?,
+        ),
+        (None, None) => None,
+    };
+
+    // CPU usage is computed between two refreshes
+    std::thread::sleep(System::MINIMUM_CPU_UPDATE_INTERVAL);
+    sys.refresh_processes();
+
+    export_processes(sys, query.as_deref(), &mut std::io::stdout().lock())?;
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
+    let args = parse_args()?;
+    let config = Config::load()?;
     let mut sys = System::new_all();
 
+    if args.export {
+        return export(&config, args, &mut sys);
+    }
+    if args.vmstat {
+        return export_vmstat(Duration::from_secs(1), &mut std::io::stdout().lock());
+    }
+
     enable_raw_mode()?;
     let mut stdout = std::io::stdout();
     stdout.execute(EnterAlternateScreen)?;
@@ -20,7 +96,7 @@ async fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    App::new().run(&mut terminal, &mut sys).await?;
+    App::new(config).run(&mut terminal, &mut sys).await?;
 
     disable_raw_mode()?;
     terminal.backend_mut().execute(LeaveAlternateScreen)?;
@@ -1,52 +1,465 @@
+use std::{collections::VecDeque, fs};
+
 use ratatui::{
+    layout::Constraint,
     style::{Color, Style},
-    widgets::{Block, Borders, Gauge},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, Gauge, GraphType, Paragraph},
 };
 use sysinfo::{System, SystemExt};
 
-pub struct MemoryGauges<'a> {
-    pub ram_gauge: Gauge<'a>,
-    pub swap_gauge: Gauge<'a>,
+use crate::{cgroup::CgroupLimits, layout::get_highlight_style, vmstat::VmStatRates};
+
+/// Gauge of the host's memory, or of reson's cgroup usage against its limit when `cgroup` is
+/// given. Without a memory limit the cgroup can use all of the host's memory.
+pub fn create_memory_gauge<'a>(sys: &System, cgroup: Option<&CgroupLimits>) -> Gauge<'a> {
+    let (total_memory, used_memory) = match cgroup {
+        Some(limits) => (
+            limits
+                .memory_limit
+                .map_or(sys.total_memory(), |limit| limit.min(sys.total_memory())),
+            limits.memory_usage.unwrap_or(sys.used_memory()),
+        ),
+        None => (sys.total_memory(), sys.used_memory()),
+    };
+    let total_memory_gb = total_memory as f64 / 1024.0 / 1024.0;
+    let used_memory_gb = used_memory as f64 / 1024.0 / 1024.0;
+    let memory_percentage = if total_memory_gb > 0.0 {
+        (used_memory_gb / total_memory_gb) * 100.0
+    } else {
+        0.0
+    };
+
+    let title = match cgroup {
+        Some(limits) => format!(
+            "Memory Usage of cgroup {}, Limit: {} MB, Used: {} MB",
+            limits.path,
+            total_memory_gb.round(),
+            used_memory_gb.round(),
+        ),
+        None => format!(
+            "Memory Usage, Total: {} MB, Used: {} MB",
+            total_memory_gb.round(),
+            used_memory_gb.round(),
+        ),
+    };
+
+    Gauge::default()
+        .block(Block::default().title(title).borders(Borders::all()))
+        .gauge_style(Style::default().fg(Color::Blue))
+        .style(Style::default().fg(Color::Blue))
+        .percent(memory_percentage.min(100.0) as u16)
+}
+
+/// Subset of `/proc/meminfo`, converted to bytes.
+#[derive(Clone, Copy, Default)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shmem: u64,
+    pub slab_reclaimable: u64,
+    pub slab_unreclaimable: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub anon_huge_pages: u64,
+    pub huge_pages_total: u64,
+    pub huge_pages_free: u64,
+    pub huge_page_size: u64,
+    pub zswap: u64,
+    pub zswapped: u64,
 }
 
-pub fn create_memory_gauges(sys: &System) -> MemoryGauges {
-    let total_memory_gb = sys.total_memory() as f64 / 1024.0 / 1024.0;
-    let used_memory_gb = sys.used_memory() as f64 / 1024.0 / 1024.0;
-    let memory_percentage = (used_memory_gb / total_memory_gb) * 100.0;
+impl MemInfo {
+    pub fn read() -> Option<MemInfo> {
+        let content = fs::read_to_string("/proc/meminfo").ok()?;
+        Some(MemInfo::parse(&content))
+    }
+
+    fn parse(content: &str) -> MemInfo {
+        let mut info = MemInfo::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let mut parts = value.split_whitespace();
+            let value: u64 = parts.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+            // HugePages_* are page counts, everything else is reported in kB
+            let bytes = match parts.next() {
+                Some("kB") => value * 1024,
+                _ => value,
+            };
+
+            match key {
+                "MemTotal" => info.total = bytes,
+                "MemFree" => info.free = bytes,
+                "MemAvailable" => info.available = bytes,
+                "Buffers" => info.buffers = bytes,
+                "Cached" => info.cached = bytes,
+                "Shmem" => info.shmem = bytes,
+                "SReclaimable" => info.slab_reclaimable = bytes,
+                "SUnreclaim" => info.slab_unreclaimable = bytes,
+                "Dirty" => info.dirty = bytes,
+                "Writeback" => info.writeback = bytes,
+                "AnonHugePages" => info.anon_huge_pages = bytes,
+                "HugePages_Total" => info.huge_pages_total = bytes,
+                "HugePages_Free" => info.huge_pages_free = bytes,
+                "Hugepagesize" => info.huge_page_size = bytes,
+                "Zswap" => info.zswap = bytes,
+                "Zswapped" => info.zswapped = bytes,
+                _ => {}
+            }
+        }
+        info
+    }
+
+    /// Memory that can't be reclaimed, calculated the same way as `free` does.
+    pub fn used(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cached)
+            .saturating_sub(self.slab_reclaimable)
+    }
 
-    let total_swap_gb = sys.total_swap() as f64 / 1024.0 / 1024.0;
-    let used_swap_gb = sys.used_swap() as f64 / 1024.0 / 1024.;
-    let swap_percentage = (used_swap_gb / total_swap_gb) * 100.0;
+    /// Page cache without shared memory, which `Cached` includes.
+    pub fn page_cache(&self) -> u64 {
+        self.cached.saturating_sub(self.shmem)
+    }
+}
+
+struct BreakdownSegment {
+    label: &'static str,
+    bytes: u64,
+    color: Color,
+}
 
-    let memory_gauge = Gauge::default()
+pub struct MemoryBreakdown<'a> {
+    pub chart: Paragraph<'a>,
+    pub max_scroll: usize,
+}
+
+pub fn create_memory_breakdown<'a>(
+    meminfo: Option<&MemInfo>,
+    vmstat: Option<&VmStatRates>,
+    layout_width: usize,
+    layout_height: usize,
+    scroll_position: usize,
+    is_selected: bool,
+) -> MemoryBreakdown<'a> {
+    // -2 for border
+    let visible_lines = layout_height.saturating_sub(2);
+    let bar_width = layout_width.saturating_sub(2);
+    let highlight_style = get_highlight_style(is_selected);
+
+    let mut lines = match meminfo {
+        Some(meminfo) => memory_breakdown_lines(meminfo, bar_width),
+        None => vec![Line::from("/proc/meminfo is not available")],
+    };
+    if let Some(vmstat) = vmstat {
+        lines.extend(vmstat_lines(vmstat));
+    }
+
+    let max_scroll = lines.len().saturating_sub(visible_lines);
+    let paragraph = Paragraph::new(lines)
         .block(
             Block::default()
-                .title(format!(
-                    "Memory Usage, Total: {} MB, Used: {} MB",
-                    total_memory_gb.round(),
-                    used_memory_gb.round(),
-                ))
-                .borders(Borders::all()),
+                .title("Memory Breakdown")
+                .style(Style::default().fg(Color::Blue))
+                .title_style(highlight_style.title)
+                .borders(Borders::all())
+                .border_style(highlight_style.border)
+                .border_type(highlight_style.border_type),
         )
-        .gauge_style(Style::default().fg(Color::Blue))
-        .style(Style::default().fg(Color::Blue))
-        .percent(memory_percentage as u16);
+        .scroll((scroll_position as u16, 0));
+
+    MemoryBreakdown {
+        chart: paragraph,
+        max_scroll,
+    }
+}
+
+fn memory_breakdown_lines<'a>(meminfo: &MemInfo, bar_width: usize) -> Vec<Line<'a>> {
+    // These add up to MemTotal, so they can be stacked in a single bar
+    let segments = [
+        BreakdownSegment {
+            label: "Used",
+            bytes: meminfo.used().saturating_sub(meminfo.slab_unreclaimable),
+            color: Color::Blue,
+        },
+        BreakdownSegment {
+            label: "Slab (unreclaimable)",
+            bytes: meminfo.slab_unreclaimable,
+            color: Color::Red,
+        },
+        BreakdownSegment {
+            label: "Shared/tmpfs",
+            bytes: meminfo.shmem,
+            color: Color::Magenta,
+        },
+        BreakdownSegment {
+            label: "Buffers",
+            bytes: meminfo.buffers,
+            color: Color::Cyan,
+        },
+        BreakdownSegment {
+            label: "Page cache",
+            bytes: meminfo.page_cache(),
+            color: Color::Yellow,
+        },
+        BreakdownSegment {
+            label: "Slab (reclaimable)",
+            bytes: meminfo.slab_reclaimable,
+            color: Color::LightYellow,
+        },
+        BreakdownSegment {
+            label: "Free",
+            bytes: meminfo.free,
+            color: Color::DarkGray,
+        },
+    ];
+
+    let total = meminfo.total.max(1) as f64;
+    let mut bar = Vec::new();
+    let mut filled = 0;
+    for (i, segment) in segments.iter().enumerate() {
+        // Last segment takes the rounding leftovers so the bar always spans the whole width
+        let width = if i == segments.len() - 1 {
+            bar_width.saturating_sub(filled)
+        } else {
+            ((segment.bytes as f64 / total) * bar_width as f64).round() as usize
+        };
+        let width = width.min(bar_width.saturating_sub(filled));
+        filled += width;
+        bar.push(Span::styled(
+            "█".repeat(width),
+            Style::default().fg(segment.color),
+        ));
+    }
+
+    let mut lines = vec![Line::from(bar)];
+    for segment in &segments {
+        lines.push(Line::from(vec![
+            Span::styled("■ ", Style::default().fg(segment.color)),
+            Span::raw(format!(
+                "{:<21}{:>8} MB {:>5.1}%",
+                segment.label,
+                segment.bytes / 1024 / 1024,
+                segment.bytes as f64 / total * 100.0
+            )),
+        ]));
+    }
+
+    lines.push(Line::from(format!(
+        "  {:<21}{:>8} MB {:>5.1}%",
+        "Available",
+        meminfo.available / 1024 / 1024,
+        meminfo.available as f64 / total * 100.0
+    )));
+    lines.push(Line::from(format!(
+        "  Dirty: {} MB, Writeback: {} MB",
+        meminfo.dirty / 1024 / 1024,
+        meminfo.writeback / 1024 / 1024
+    )));
+    lines.push(Line::from(format!(
+        "  Huge pages: {}/{} free ({} kB each), THP: {} MB",
+        meminfo.huge_pages_free,
+        meminfo.huge_pages_total,
+        meminfo.huge_page_size / 1024,
+        meminfo.anon_huge_pages / 1024 / 1024
+    )));
 
-    let swap_gauge = Gauge::default()
+    lines
+}
+
+fn vmstat_lines<'a>(vmstat: &VmStatRates) -> Vec<Line<'a>> {
+    let mut lines = vec![Line::from("Paging activity:")];
+    for (name, value, unit) in vmstat.metrics() {
+        lines.push(Line::from(format!(
+            "  {:<21}{:>10.1} {}",
+            name, value, unit
+        )));
+    }
+    lines.push(Line::from(format!(
+        "  {:<21}{:>10}",
+        "OOM kills since boot", vmstat.total_oom_kills
+    )));
+    lines
+}
+
+#[derive(Clone, Copy)]
+struct MemorySample {
+    used: u64,
+    cached: u64,
+    swap: u64,
+    // First sample of a run where swap usage keeps growing
+    swap_rising: bool,
+}
+
+/// Used, cached and swap usage sampled on every refresh, one sample per second.
+pub struct MemoryHistory {
+    samples: VecDeque<MemorySample>,
+    window: usize,
+    total_memory: u64,
+    total_swap: u64,
+}
+
+impl MemoryHistory {
+    pub fn new(window: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(2),
+            total_memory: 0,
+            total_swap: 0,
+        }
+    }
+
+    pub fn refresh(&mut self, sys: &System, meminfo: Option<&MemInfo>) {
+        let (used, cached) = match meminfo {
+            Some(meminfo) => (
+                meminfo.used(),
+                meminfo.buffers + meminfo.cached + meminfo.slab_reclaimable,
+            ),
+            None => (sys.used_memory(), 0),
+        };
+        let swap = sys.used_swap();
+
+        let swap_rising = match self.samples.back() {
+            Some(previous) => swap > previous.swap && !self.swap_was_rising(),
+            None => false,
+        };
+
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(MemorySample {
+            used,
+            cached,
+            swap,
+            swap_rising,
+        });
+        self.total_memory = sys.total_memory();
+        self.total_swap = sys.total_swap();
+    }
+
+    fn swap_was_rising(&self) -> bool {
+        let mut last_two = self.samples.iter().rev().take(2);
+        match (last_two.next(), last_two.next()) {
+            (Some(last), Some(before_last)) => last.swap > before_last.swap,
+            _ => false,
+        }
+    }
+
+    pub fn chart_points(&self) -> MemoryHistoryPoints {
+        let mut points = MemoryHistoryPoints {
+            used: Vec::with_capacity(self.samples.len()),
+            cached: Vec::with_capacity(self.samples.len()),
+            swap: Vec::with_capacity(self.samples.len()),
+            swap_rising: Vec::new(),
+        };
+
+        let newest = self.samples.len().saturating_sub(1) as f64;
+        let max = bytes_to_gb(self.max_bytes());
+        for (i, sample) in self.samples.iter().enumerate() {
+            // x is "seconds ago", so the newest sample sits at 0 on the right edge
+            let x = i as f64 - newest;
+            points.used.push((x, bytes_to_gb(sample.used)));
+            points.cached.push((x, bytes_to_gb(sample.cached)));
+            points.swap.push((x, bytes_to_gb(sample.swap)));
+
+            if sample.swap_rising {
+                // Draw the marker as a dotted vertical line through the whole chart
+                let steps = 20;
+                for step in 0..=steps {
+                    points
+                        .swap_rising
+                        .push((x, max * step as f64 / steps as f64));
+                }
+            }
+        }
+
+        points
+    }
+
+    fn max_bytes(&self) -> u64 {
+        self.total_memory.max(self.total_swap).max(1)
+    }
+}
+
+pub struct MemoryHistoryPoints {
+    used: Vec<(f64, f64)>,
+    cached: Vec<(f64, f64)>,
+    swap: Vec<(f64, f64)>,
+    swap_rising: Vec<(f64, f64)>,
+}
+
+fn bytes_to_gb(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0 / 1024.0
+}
+
+pub fn create_memory_history_chart<'a>(
+    history: &MemoryHistory,
+    points: &'a MemoryHistoryPoints,
+) -> Chart<'a> {
+    let datasets = vec![
+        Dataset::default()
+            .name("Used")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Blue))
+            .data(&points.used),
+        Dataset::default()
+            .name("Cached")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Yellow))
+            .data(&points.cached),
+        Dataset::default()
+            .name("Swap")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::LightMagenta))
+            .data(&points.swap),
+        Dataset::default()
+            .name("Swap rising")
+            .marker(Marker::Dot)
+            .graph_type(GraphType::Scatter)
+            .style(Style::default().fg(Color::Red))
+            .data(&points.swap_rising),
+    ];
+
+    let window = history.window as f64;
+    let max_gb = bytes_to_gb(history.max_bytes());
+
+    Chart::new(datasets)
         .block(
             Block::default()
-                .title(format!(
-                    "Swap Usage, Total: {} MB, Used: {} MB",
-                    total_swap_gb, used_swap_gb
-                ))
+                .title(format!("Memory History (last {}s)", history.window))
+                .style(Style::default().fg(Color::Blue))
                 .borders(Borders::all()),
         )
-        .gauge_style(Style::default().fg(Color::LightMagenta))
-        .style(Style::default().fg(Color::LightMagenta))
-        .percent(swap_percentage as u16);
-
-    MemoryGauges {
-        ram_gauge: memory_gauge,
-        swap_gauge,
-    }
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([-(window - 1.0), 0.0])
+                .labels(vec![
+                    Span::raw(format!("-{}s", history.window)),
+                    Span::raw(format!("-{}s", history.window / 2)),
+                    Span::raw("now"),
+                ]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, max_gb])
+                .labels(vec![
+                    Span::raw("0"),
+                    Span::raw(format!("{:.1}", max_gb / 2.0)),
+                    Span::raw(format!("{:.1} GB", max_gb)),
+                ]),
+        )
+        .hidden_legend_constraints((Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)))
 }
@@ -0,0 +1,319 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ratatui::{
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph, Sparkline},
+};
+
+const HISTORY_LENGTH: usize = 240;
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+#[derive(Clone, Copy, Default)]
+pub struct PressureAverages {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ResourcePressure {
+    pub some: PressureAverages,
+    pub full: Option<PressureAverages>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct PressureStats {
+    pub cpu: Option<ResourcePressure>,
+    pub memory: Option<ResourcePressure>,
+    pub io: Option<ResourcePressure>,
+}
+
+impl PressureStats {
+    /// Reads `/proc/pressure/*` or, when a cgroup path is given, the cgroup's `*.pressure` files.
+    pub fn read(cgroup: Option<&str>) -> PressureStats {
+        let root = cgroup2_root();
+        PressureStats {
+            cpu: read_resource_pressure(pressure_path(&root, cgroup, "cpu")),
+            memory: read_resource_pressure(pressure_path(&root, cgroup, "memory")),
+            io: read_resource_pressure(pressure_path(&root, cgroup, "io")),
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.cpu.is_some() || self.memory.is_some() || self.io.is_some()
+    }
+}
+
+/// Hybrid hierarchies mount cgroup2 under `unified`, next to the v1 controllers.
+fn cgroup2_root() -> PathBuf {
+    let root = PathBuf::from(CGROUP_ROOT);
+    if root.join("cgroup.controllers").exists() {
+        root
+    } else {
+        root.join("unified")
+    }
+}
+
+fn pressure_path(cgroup2_root: &Path, cgroup: Option<&str>, resource: &str) -> PathBuf {
+    match cgroup {
+        Some(cgroup) => cgroup2_root
+            .join(cgroup.trim_start_matches('/'))
+            .join(format!("{}.pressure", resource)),
+        None => PathBuf::from("/proc/pressure").join(resource),
+    }
+}
+
+/// Cgroups below the cgroup2 root that expose pressure files, as sorted absolute cgroup paths.
+pub fn list_pressure_cgroups() -> Vec<String> {
+    find_pressure_cgroups(&cgroup2_root())
+}
+
+fn find_pressure_cgroups(cgroup2_root: &Path) -> Vec<String> {
+    let mut cgroups = Vec::new();
+    let mut pending = vec![cgroup2_root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                continue;
+            }
+            if path.join("cpu.pressure").exists() {
+                if let Ok(relative) = path.strip_prefix(cgroup2_root) {
+                    cgroups.push(format!("/{}", relative.display()));
+                }
+            }
+            pending.push(path);
+        }
+    }
+
+    cgroups.sort();
+    cgroups
+}
+
+fn read_resource_pressure(path: PathBuf) -> Option<ResourcePressure> {
+    let content = fs::read_to_string(path).ok()?;
+    parse_resource_pressure(&content)
+}
+
+fn parse_resource_pressure(content: &str) -> Option<ResourcePressure> {
+    let mut some = None;
+    let mut full = None;
+
+    for line in content.lines() {
+        let mut parts = line.split_whitespace();
+        let kind = parts.next();
+        let mut averages = PressureAverages::default();
+        for part in parts {
+            let Some((key, value)) = part.split_once('=') else {
+                continue;
+            };
+            let value = value.parse().unwrap_or(0.0);
+            match key {
+                "avg10" => averages.avg10 = value,
+                "avg60" => averages.avg60 = value,
+                "avg300" => averages.avg300 = value,
+                _ => {}
+            }
+        }
+
+        match kind {
+            Some("some") => some = Some(averages),
+            Some("full") => full = Some(averages),
+            _ => {}
+        }
+    }
+
+    Some(ResourcePressure { some: some?, full })
+}
+
+/// Latest pressure sample plus the `some avg10` history used for the sparklines.
+pub struct PressureHistory {
+    pub current: PressureStats,
+    pub cgroup: Option<String>,
+    cpu: Vec<u64>,
+    memory: Vec<u64>,
+    io: Vec<u64>,
+}
+
+impl PressureHistory {
+    pub fn new() -> Self {
+        Self {
+            current: PressureStats::default(),
+            cgroup: None,
+            cpu: Vec::with_capacity(HISTORY_LENGTH),
+            memory: Vec::with_capacity(HISTORY_LENGTH),
+            io: Vec::with_capacity(HISTORY_LENGTH),
+        }
+    }
+
+    pub fn refresh(&mut self, cgroup: Option<&str>) {
+        // Switching between the system and a cgroup makes the old history meaningless
+        if self.cgroup.as_deref() != cgroup {
+            self.cgroup = cgroup.map(str::to_string);
+            self.cpu.clear();
+            self.memory.clear();
+            self.io.clear();
+        }
+
+        self.current = PressureStats::read(cgroup);
+        push_sample(&mut self.cpu, self.current.cpu);
+        push_sample(&mut self.memory, self.current.memory);
+        push_sample(&mut self.io, self.current.io);
+    }
+}
+
+fn push_sample(history: &mut Vec<u64>, pressure: Option<ResourcePressure>) {
+    if history.len() == HISTORY_LENGTH {
+        history.remove(0);
+    }
+
+    // Sparklines only take integers, so keep two decimal places of the percentage
+    let value = pressure.map_or(0.0, |pressure| pressure.some.avg10);
+    history.push((value * 100.0).round() as u64);
+}
+
+pub struct PressureRow<'a> {
+    pub summary: Paragraph<'a>,
+    pub sparkline: Sparkline<'a>,
+}
+
+pub struct PressureWidget<'a> {
+    pub block: Block<'a>,
+    pub cpu: PressureRow<'a>,
+    pub memory: PressureRow<'a>,
+    pub io: PressureRow<'a>,
+}
+
+pub fn create_pressure_widget(
+    history: &PressureHistory,
+    layout_width: usize,
+) -> PressureWidget<'_> {
+    let source = match &history.cgroup {
+        Some(cgroup) => format!("cgroup {}", cgroup),
+        None => "System".to_string(),
+    };
+    let title = if history.current.is_available() {
+        format!("Pressure (avg10/60/300 %), {}", source)
+    } else {
+        format!("Pressure, {}: not available", source)
+    };
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::all())
+        .style(Style::default().fg(Color::LightRed));
+
+    // -2 for border
+    let visible_samples = layout_width.saturating_sub(2);
+    PressureWidget {
+        block,
+        cpu: create_pressure_row("CPU", history.current.cpu, &history.cpu, visible_samples),
+        memory: create_pressure_row(
+            "MEM",
+            history.current.memory,
+            &history.memory,
+            visible_samples,
+        ),
+        io: create_pressure_row("IO", history.current.io, &history.io, visible_samples),
+    }
+}
+
+fn create_pressure_row<'a>(
+    label: &str,
+    pressure: Option<ResourcePressure>,
+    history: &'a [u64],
+    visible_samples: usize,
+) -> PressureRow<'a> {
+    let summary = match pressure {
+        Some(pressure) => {
+            let full = pressure
+                .full
+                .map_or("-".to_string(), |full| format_averages(&full));
+            format!(
+                "{:<4}some {} full {}",
+                label,
+                format_averages(&pressure.some),
+                full
+            )
+        }
+        None => format!("{:<4}-", label),
+    };
+
+    let data = &history[history.len().saturating_sub(visible_samples)..];
+    // Keep at least 10% as the scale so idle noise doesn't fill the whole sparkline
+    let max = data.iter().copied().max().unwrap_or(0).max(1000);
+
+    PressureRow {
+        summary: Paragraph::new(summary),
+        sparkline: Sparkline::default()
+            .style(Style::default().fg(Color::LightRed))
+            .data(data)
+            .max(max),
+    }
+}
+
+fn format_averages(averages: &PressureAverages) -> String {
+    format!(
+        "{:.2}/{:.2}/{:.2}",
+        averages.avg10, averages.avg60, averages.avg300
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_system_or_cgroup_pressure_files() {
+        let root = Path::new("/sys/fs/cgroup");
+        assert_eq!(
+            pressure_path(root, None, "cpu"),
+            PathBuf::from("/proc/pressure/cpu")
+        );
+        assert_eq!(
+            pressure_path(root, Some("/system.slice/sshd.service"), "io"),
+            PathBuf::from("/sys/fs/cgroup/system.slice/sshd.service/io.pressure")
+        );
+    }
+
+    #[test]
+    fn lists_cgroups_with_pressure_files() {
+        let root = std::env::temp_dir().join(format!("reson-pressure-{}", std::process::id()));
+        for dir in ["system.slice/sshd.service", "user.slice", "no-psi"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for dir in ["system.slice", "system.slice/sshd.service", "user.slice"] {
+            fs::write(root.join(dir).join("cpu.pressure"), "").unwrap();
+        }
+
+        let cgroups = find_pressure_cgroups(&root);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            cgroups,
+            ["/system.slice", "/system.slice/sshd.service", "/user.slice"]
+        );
+    }
+
+    #[test]
+    fn parses_pressure_file() {
+        let pressure = parse_resource_pressure(
+            "some avg10=1.50 avg60=0.75 avg300=0.10 total=12345\n\
+             full avg10=0.25 avg60=0.00 avg300=0.00 total=678\n",
+        )
+        .unwrap();
+        assert_eq!(pressure.some.avg10, 1.5);
+        assert_eq!(pressure.some.avg300, 0.1);
+        assert_eq!(pressure.full.unwrap().avg10, 0.25);
+
+        // /proc/pressure/cpu has no full line before Linux 5.13
+        let cpu =
+            parse_resource_pressure("some avg10=0.00 avg60=0.00 avg300=0.00 total=0").unwrap();
+        assert!(cpu.full.is_none());
+    }
+}
@@ -1,26 +1,185 @@
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
 use ratatui::{
     layout::Constraint,
-    style::{Color, Style},
-    widgets::{Block, Borders, Row, Table},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Row, Table, TableState},
 };
-use sysinfo::{ProcessExt, System, SystemExt, UserExt};
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, PidExt, Process, ProcessExt, System, SystemExt, Uid, UserExt};
 
+use crate::container::cgroup_label;
+use crate::details::format_timestamp;
+use crate::filter::ProcessFilter;
 use crate::layout::get_highlight_style;
+use crate::procstat::{read_cgroup_path, ProcStatCache};
+use crate::query::state_name;
 
 pub struct ProcessesTable<'a_> {
     pub chart: Table<'a_>,
+    // The table only borrows its widths, so they are applied where it's rendered
+    pub widths: Vec<Constraint>,
     pub max_scroll: usize,
+    pub scroll_position: usize,
+    // Processes in the visible window, the background readers only look at these
+    pub visible_pids: Vec<Pid>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ProcessColumn {
     User,
-    PID,
-    PPID,
-    CPU,
+    Pid,
+    Ppid,
+    Cpu,
     Memory,
     Time,
     Command,
+    State,
+    Threads,
+    Nice,
+    Priority,
+    VirtualMemory,
+    Swap,
+    DiskRead,
+    DiskWrite,
+    StartTime,
+    CpuTime,
+    LastCpu,
+    Cmdline,
+    ReadRate,
+    WriteRate,
+    IoRate,
+    Pss,
+    Uss,
+    Container,
+}
+
+impl ProcessColumn {
+    pub const ALL: [ProcessColumn; 25] = [
+        ProcessColumn::User,
+        ProcessColumn::Pid,
+        ProcessColumn::Ppid,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::Time,
+        ProcessColumn::Command,
+        ProcessColumn::State,
+        ProcessColumn::Threads,
+        ProcessColumn::Nice,
+        ProcessColumn::Priority,
+        ProcessColumn::VirtualMemory,
+        ProcessColumn::Swap,
+        ProcessColumn::DiskRead,
+        ProcessColumn::DiskWrite,
+        ProcessColumn::StartTime,
+        ProcessColumn::CpuTime,
+        ProcessColumn::LastCpu,
+        ProcessColumn::Cmdline,
+        ProcessColumn::ReadRate,
+        ProcessColumn::WriteRate,
+        ProcessColumn::IoRate,
+        ProcessColumn::Pss,
+        ProcessColumn::Uss,
+        ProcessColumn::Container,
+    ];
+
+    pub const DEFAULT: [ProcessColumn; 7] = [
+        ProcessColumn::User,
+        ProcessColumn::Pid,
+        ProcessColumn::Ppid,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::Time,
+        ProcessColumn::Command,
+    ];
+
+    /// Columns of the iotop-style IO mode.
+    pub const IO: [ProcessColumn; 6] = [
+        ProcessColumn::Pid,
+        ProcessColumn::User,
+        ProcessColumn::ReadRate,
+        ProcessColumn::WriteRate,
+        ProcessColumn::IoRate,
+        ProcessColumn::Cmdline,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ProcessColumn::User => "User",
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::Ppid => "PPID",
+            ProcessColumn::Cpu => "CPU%",
+            ProcessColumn::Memory => "MEM(MB)",
+            ProcessColumn::Time => "Time",
+            ProcessColumn::Command => "Command",
+            ProcessColumn::State => "State",
+            ProcessColumn::Threads => "Threads",
+            ProcessColumn::Nice => "Nice",
+            ProcessColumn::Priority => "Prio",
+            ProcessColumn::VirtualMemory => "VIRT(MB)",
+            ProcessColumn::Swap => "Swap(MB)",
+            ProcessColumn::DiskRead => "Read(MB)",
+            ProcessColumn::DiskWrite => "Write(MB)",
+            ProcessColumn::StartTime => "Started",
+            ProcessColumn::CpuTime => "CPU Time",
+            ProcessColumn::LastCpu => "Last CPU",
+            ProcessColumn::Cmdline => "Cmdline",
+            ProcessColumn::ReadRate => "Read/s",
+            ProcessColumn::WriteRate => "Write/s",
+            ProcessColumn::IoRate => "IO/s",
+            ProcessColumn::Pss => "PSS(MB)",
+            ProcessColumn::Uss => "USS(MB)",
+            ProcessColumn::Container => "Container",
+        }
+    }
+
+    /// Width of the column, None for the command columns that share what's left.
+    fn width(self) -> Option<u16> {
+        match self {
+            ProcessColumn::Command | ProcessColumn::Cmdline => None,
+            ProcessColumn::User => Some(12),
+            ProcessColumn::StartTime => Some(19),
+            ProcessColumn::Container => Some(25),
+            ProcessColumn::State => Some(10),
+            ProcessColumn::Time | ProcessColumn::CpuTime => Some(10),
+            ProcessColumn::Nice | ProcessColumn::Priority => Some(5),
+            ProcessColumn::ReadRate | ProcessColumn::WriteRate | ProcessColumn::IoRate => Some(11),
+            _ => Some(9),
+        }
+    }
+
+    /// Columns read from `/proc/<pid>/stat`.
+    pub fn needs_stat(self) -> bool {
+        matches!(
+            self,
+            ProcessColumn::Threads
+                | ProcessColumn::Nice
+                | ProcessColumn::Priority
+                | ProcessColumn::CpuTime
+                | ProcessColumn::LastCpu
+        )
+    }
+
+    /// Columns read from `/proc/<pid>/status`.
+    pub fn needs_swap(self) -> bool {
+        self == ProcessColumn::Swap
+    }
+
+    /// Columns derived from `/proc/<pid>/cgroup`.
+    pub fn needs_cgroup(self) -> bool {
+        self == ProcessColumn::Container
+    }
+
+    /// Columns read from `/proc/<pid>/smaps_rollup`, in the background and for visible rows.
+    pub fn needs_smaps(self) -> bool {
+        matches!(self, ProcessColumn::Pss | ProcessColumn::Uss)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,209 +188,1308 @@ pub enum SortDirection {
     Descending,
 }
 
-pub fn create_processes_table(
-    sys: &System,
-    layout_height: usize,
-    scroll_position: usize,
-    is_selected: bool,
-    sort_by: Option<(ProcessColumn, SortDirection)>,
-) -> ProcessesTable<'_> {
-    // -2 for border
-    let visible_lines = layout_height - 2;
-    let highlight_style = get_highlight_style(is_selected);
+impl SortDirection {
+    fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortDirection::Ascending => ordering,
+            SortDirection::Descending => ordering.reverse(),
+        }
+    }
+}
 
-    let mut processes: Vec<_> = sys.processes().values().collect();
-    let total_memory = sys.total_memory() as f64;
+/// Columns the processes are sorted by, in priority order. Empty sorts by the combined CPU and
+/// memory score.
+pub type SortKeys = Vec<(ProcessColumn, SortDirection)>;
 
-    match sort_by {
-        Some((ProcessColumn::User, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_user = a
-                    .user_id()
-                    .and_then(|id| sys.get_user_by_id(&id))
-                    .map(|user| user.name().to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                let b_user = b
-                    .user_id()
-                    .and_then(|id| sys.get_user_by_id(&id))
-                    .map(|user| user.name().to_string())
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                match direction {
-                    SortDirection::Ascending => a_user.cmp(&b_user),
-                    SortDirection::Descending => b_user.cmp(&a_user),
-                }
-            });
+/// Tree mode settings, processes are nested under their parents when enabled.
+#[derive(Default)]
+pub struct ProcessTreeState {
+    pub enabled: bool,
+    pub show_subtree_totals: bool,
+    pub collapsed: HashSet<Pid>,
+}
+
+impl ProcessTreeState {
+    pub fn collapse(&mut self, pid: Pid) {
+        self.collapsed.insert(pid);
+    }
+
+    pub fn expand(&mut self, pid: Pid) {
+        self.collapsed.remove(&pid);
+    }
+}
+
+/// What the processes are grouped by, each group collapses into one row with its totals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessGrouping {
+    User,
+    Command,
+    Cgroup,
+    Container,
+}
+
+impl ProcessGrouping {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProcessGrouping::User => "user",
+            ProcessGrouping::Command => "command",
+            ProcessGrouping::Cgroup => "cgroup",
+            ProcessGrouping::Container => "container",
         }
-        Some((ProcessColumn::PID, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_pid = a.pid();
-                let b_pid = b.pid();
+    }
+}
 
-                match direction {
-                    SortDirection::Ascending => a_pid.cmp(&b_pid),
-                    SortDirection::Descending => b_pid.cmp(&a_pid),
-                }
-            });
+/// Grouping mode settings, groups are listed collapsed until they're expanded.
+#[derive(Default)]
+pub struct ProcessGroupState {
+    pub grouping: Option<ProcessGrouping>,
+    // Keys of the groups whose members are listed
+    pub expanded: HashSet<String>,
+}
+
+impl ProcessGroupState {
+    /// Switches to the next grouping, the last one turns grouping off.
+    pub fn cycle(&mut self) {
+        self.grouping = match self.grouping {
+            None => Some(ProcessGrouping::User),
+            Some(ProcessGrouping::User) => Some(ProcessGrouping::Command),
+            Some(ProcessGrouping::Command) => Some(ProcessGrouping::Cgroup),
+            Some(ProcessGrouping::Cgroup) => Some(ProcessGrouping::Container),
+            Some(ProcessGrouping::Container) => None,
+        };
+        self.expanded.clear();
+    }
+
+    pub fn toggle(&mut self, key: &str) {
+        if !self.expanded.remove(key) {
+            self.expanded.insert(key.to_string());
         }
-        Some((ProcessColumn::PPID, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_ppid = a.parent();
-                let b_ppid = b.parent();
+    }
 
-                match direction {
-                    SortDirection::Ascending => a_ppid.cmp(&b_ppid),
-                    SortDirection::Descending => b_ppid.cmp(&a_ppid),
-                }
-            });
-        }
-        Some((ProcessColumn::CPU, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_cpu = a.cpu_usage();
-                let b_cpu = b.cpu_usage();
-                match direction {
-                    SortDirection::Ascending => a_cpu
-                        .partial_cmp(&b_cpu)
-                        .unwrap_or(std::cmp::Ordering::Equal),
-                    SortDirection::Descending => b_cpu
-                        .partial_cmp(&a_cpu)
-                        .unwrap_or(std::cmp::Ordering::Equal),
-                }
-            });
+    pub fn expand(&mut self, key: &str) {
+        self.expanded.insert(key.to_string());
+    }
+
+    pub fn collapse(&mut self, key: &str) {
+        self.expanded.remove(key);
+    }
+}
+
+/// Key of the group the process belongs to.
+pub fn group_key(
+    process: &Process,
+    grouping: ProcessGrouping,
+    users: &UserNames,
+    stats: &ProcStatCache,
+) -> String {
+    match grouping {
+        ProcessGrouping::User => users.name(process).to_string(),
+        ProcessGrouping::Command => process.name().to_string(),
+        // Read directly until the next refresh caches them
+        ProcessGrouping::Cgroup => stats
+            .cgroup(process.pid())
+            .map(str::to_string)
+            .or_else(|| read_cgroup_path(process.pid()))
+            .unwrap_or_else(|| "unknown".to_string()),
+        ProcessGrouping::Container => workload_label(process, stats),
+    }
+}
+
+/// Container or systemd unit of the process, `-` when it's in neither.
+fn workload_label(process: &Process, stats: &ProcStatCache) -> String {
+    // Read directly until the next refresh caches the cgroup
+    match stats.cgroup(process.pid()) {
+        Some(path) => cgroup_label(path),
+        None => read_cgroup_path(process.pid()).map_or("-".to_string(), |path| cgroup_label(&path)),
+    }
+}
+
+/// A process kept at the top of the table. The start time tells it apart from a later process
+/// that reuses its PID.
+struct PinnedProcess {
+    pid: Pid,
+    name: String,
+    start_time: u64,
+    // Seconds since the Unix epoch, set on the first refresh it was gone
+    exited_at: Option<u64>,
+}
+
+/// Processes pinned to the top of the table, in the order they were pinned. They stay listed
+/// after exiting, with their exit time, until unpinned.
+#[derive(Default)]
+pub struct ProcessPins {
+    pinned: Vec<PinnedProcess>,
+}
+
+impl ProcessPins {
+    /// Pins the process or unpins it if it already is, returns whether it's pinned now.
+    pub fn toggle(&mut self, process: &Process) -> bool {
+        if let Some(index) = self.pinned.iter().position(|pinned| {
+            pinned.exited_at.is_none()
+                && pinned.pid == process.pid()
+                && pinned.start_time == process.start_time()
+        }) {
+            self.pinned.remove(index);
+            return false;
         }
-        Some((ProcessColumn::Memory, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_mem = a.memory();
-                let b_mem = b.memory();
+        self.pinned.push(PinnedProcess {
+            pid: process.pid(),
+            name: process.name().to_string(),
+            start_time: process.start_time(),
+            exited_at: None,
+        });
+        true
+    }
 
-                match direction {
-                    SortDirection::Ascending => a_mem.cmp(&b_mem),
-                    SortDirection::Descending => b_mem.cmp(&a_mem),
-                }
-            });
+    /// Unpins the entry at the position of the pinned rows.
+    pub fn remove(&mut self, index: usize) {
+        if index < self.pinned.len() {
+            self.pinned.remove(index);
         }
-        Some((ProcessColumn::Time, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_time = a.start_time();
-                let b_time = b.start_time();
+    }
 
-                match direction {
-                    SortDirection::Ascending => a_time.cmp(&b_time),
-                    SortDirection::Descending => b_time.cmp(&a_time),
-                }
-            });
+    /// Notes the exit time of pinned processes that are gone, returns their names.
+    pub fn refresh(&mut self, sys: &System) -> Vec<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let mut exited = Vec::new();
+        for pinned in self.pinned.iter_mut().filter(|p| p.exited_at.is_none()) {
+            let alive = sys
+                .process(pinned.pid)
+                .is_some_and(|process| process.start_time() == pinned.start_time);
+            if !alive {
+                pinned.exited_at = Some(now);
+                exited.push(format!("{} ({})", pinned.name, pinned.pid));
+            }
         }
-        Some((ProcessColumn::Command, direction)) => {
-            processes.sort_by(|a, b| {
-                let a_cmd = a.name();
-                let b_cmd = b.name();
+        exited
+    }
 
-                match direction {
-                    SortDirection::Ascending => a_cmd.cmp(&b_cmd),
-                    SortDirection::Descending => b_cmd.cmp(&a_cmd),
-                }
-            });
+    fn live_pids(&self) -> HashSet<Pid> {
+        self.pinned
+            .iter()
+            .filter(|pinned| pinned.exited_at.is_none())
+            .map(|pinned| pinned.pid)
+            .collect()
+    }
+}
+
+/// Processes marked for batch actions. Marks are kept by PID, with the start time so a new
+/// process reusing the PID of a marked one isn't marked.
+#[derive(Default)]
+pub struct ProcessMarks {
+    marked: HashMap<Pid, u64>,
+}
+
+impl ProcessMarks {
+    pub fn toggle(&mut self, process: &Process) {
+        if self.marked.remove(&process.pid()).is_none() {
+            self.marked.insert(process.pid(), process.start_time());
         }
-        None => {
-            processes.sort_by(|a, b| {
-                let a_cpu_score = a.cpu_usage() as f64;
-                let b_cpu_score = b.cpu_usage() as f64;
+    }
 
-                let a_mem_score = (a.memory() as f64 / total_memory) * 100.0;
-                let b_mem_score = (b.memory() as f64 / total_memory) * 100.0;
+    pub fn mark(&mut self, process: &Process) {
+        self.marked.insert(process.pid(), process.start_time());
+    }
 
-                let a_combined = a_cpu_score + a_mem_score;
-                let b_combined = b_cpu_score + b_mem_score;
+    pub fn clear(&mut self) {
+        self.marked.clear();
+    }
+
+    /// Forgets the marked processes that exited.
+    pub fn refresh(&mut self, sys: &System) {
+        self.marked.retain(|pid, start_time| {
+            sys.process(*pid)
+                .is_some_and(|process| process.start_time() == *start_time)
+        });
+    }
 
-                b_combined
-                    .partial_cmp(&a_combined)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
+    pub fn is_marked(&self, pid: Pid) -> bool {
+        self.marked.contains_key(&pid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.marked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.marked.is_empty()
+    }
+
+    /// Marked PIDs in ascending order.
+    pub fn pids(&self) -> Vec<Pid> {
+        let mut pids: Vec<Pid> = self.marked.keys().copied().collect();
+        pids.sort();
+        pids
+    }
+}
+
+/// Cursor of the processes table. It sticks to a PID, so refreshes and re-sorts don't move it
+/// to another process.
+pub struct ProcessSelection {
+    pub pid: Option<Pid>,
+    // Key of the selected group row, pid is None while a group is selected
+    pub group: Option<String>,
+    // Used to pick the neighbouring row when the selected process goes away
+    index: usize,
+    pub table_state: TableState,
+}
+
+impl ProcessSelection {
+    pub fn new() -> Self {
+        Self {
+            pid: None,
+            group: None,
+            index: 0,
+            table_state: TableState::default(),
         }
     }
 
-    let headers = vec!["User", "PID", "PPID", "CPU%", "MEM(MB)", "Time", "Command"];
+    pub fn select_next(&mut self) {
+        self.index = self.index.saturating_add(1);
+        self.pid = None;
+        self.group = None;
+    }
+
+    pub fn select_prev(&mut self) {
+        self.index = self.index.saturating_sub(1);
+        self.pid = None;
+        self.group = None;
+    }
+
+    /// Selects the group the process is in, for when its group is collapsed.
+    pub fn select_group(&mut self, key: String) {
+        self.pid = None;
+        self.group = Some(key);
+    }
+
+    fn resolve(&mut self, rows: &[ViewRow]) {
+        let position = rows.iter().position(|row| match &row.group {
+            Some(group) => self.group.as_ref() == Some(group),
+            None => self.pid.is_some() && self.pid == row.pid,
+        });
 
-    let mut header_cells = Vec::new();
-    for (i, &header_text) in headers.iter().enumerate() {
-        let column = match i {
-            0 => ProcessColumn::User,
-            1 => ProcessColumn::PID,
-            2 => ProcessColumn::PPID,
-            3 => ProcessColumn::CPU,
-            4 => ProcessColumn::Memory,
-            5 => ProcessColumn::Time,
-            6 => ProcessColumn::Command,
-            _ => ProcessColumn::User, // Fallback
+        self.index = match position {
+            Some(position) => position,
+            None => self.index.min(rows.len().saturating_sub(1)),
+        };
+        (self.pid, self.group) = match rows.get(self.index) {
+            Some(row) => (row.pid, row.group.clone()),
+            None => (None, None),
         };
+    }
 
-        let header_with_indicator = match sort_by {
-            Some((current_col, direction)) if current_col == column => match direction {
-                SortDirection::Ascending => format!("{}↑", header_text),
-                SortDirection::Descending => format!("{}↓", header_text),
-            },
-            _ => header_text.to_string(),
+    /// Keeps the selected row inside the visible window. The first `fixed_rows` rows are always
+    /// shown and don't scroll.
+    fn scroll_position(
+        &self,
+        scroll_position: usize,
+        visible_lines: usize,
+        fixed_rows: usize,
+    ) -> usize {
+        let Some(index) = self.index.checked_sub(fixed_rows) else {
+            return scroll_position;
         };
+        if index < scroll_position {
+            index
+        } else if index >= scroll_position + visible_lines {
+            index + 1 - visible_lines
+        } else {
+            scroll_position
+        }
+    }
+}
+
+struct ProcessRow<'a> {
+    process: &'a Process,
+    // Tree branches drawn in front of the command, empty in the flat list
+    prefix: String,
+    // CPU and memory of the process plus all of its descendants
+    subtree_usage: Option<(f32, u64)>,
+}
+
+/// Summary row of a group of processes, with the totals of its members.
+struct GroupRow {
+    key: String,
+    count: usize,
+    cpu: f32,
+    memory: u64,
+    read_rate: f64,
+    write_rate: f64,
+    disk_read: u64,
+    disk_written: u64,
+    expanded: bool,
+}
+
+enum TableRow<'a> {
+    Process(ProcessRow<'a>),
+    Group(GroupRow),
+}
+
+/// Value a column is sorted by.
+#[derive(PartialEq, PartialOrd)]
+enum SortValue {
+    Number(f64),
+    Text(String),
+}
+
+fn sort_value(
+    process: &Process,
+    column: ProcessColumn,
+    users: &UserNames,
+    stats: &ProcStatCache,
+) -> SortValue {
+    let stat = stats.stat(process.pid());
+    let io_rate = stats.io_rate(process.pid());
+    let smaps = stats.smaps(process.pid());
+    let number = |value: Option<f64>| SortValue::Number(value.unwrap_or(-1.0));
+
+    match column {
+        ProcessColumn::User => SortValue::Text(users.name(process).to_string()),
+        ProcessColumn::Pid => SortValue::Number(process.pid().as_u32() as f64),
+        ProcessColumn::Ppid => number(process.parent().map(|ppid| ppid.as_u32() as f64)),
+        ProcessColumn::Cpu => SortValue::Number(process.cpu_usage() as f64),
+        ProcessColumn::Memory => SortValue::Number(process.memory() as f64),
+        // Sorted by when the process started, which is the reverse of how long it runs
+        ProcessColumn::Time | ProcessColumn::StartTime => {
+            SortValue::Number(process.start_time() as f64)
+        }
+        ProcessColumn::Command => SortValue::Text(process.name().to_string()),
+        ProcessColumn::State => SortValue::Text(state_name(process.status()).to_string()),
+        ProcessColumn::Threads => number(stat.map(|stat| stat.threads as f64)),
+        ProcessColumn::Nice => number(stat.map(|stat| stat.nice as f64)),
+        ProcessColumn::Priority => number(stat.map(|stat| stat.priority as f64)),
+        ProcessColumn::VirtualMemory => SortValue::Number(process.virtual_memory() as f64),
+        ProcessColumn::Swap => number(stats.swap(process.pid()).map(|swap| swap as f64)),
+        ProcessColumn::DiskRead => SortValue::Number(process.disk_usage().total_read_bytes as f64),
+        ProcessColumn::DiskWrite => {
+            SortValue::Number(process.disk_usage().total_written_bytes as f64)
+        }
+        ProcessColumn::CpuTime => number(stat.map(|stat| stat.cpu_time())),
+        ProcessColumn::LastCpu => number(stat.map(|stat| stat.last_cpu as f64)),
+        ProcessColumn::Cmdline => SortValue::Text(command_line(process)),
+        ProcessColumn::ReadRate => number(io_rate.map(|rate| rate.read)),
+        ProcessColumn::WriteRate => number(io_rate.map(|rate| rate.write)),
+        ProcessColumn::IoRate => number(io_rate.map(|rate| rate.total())),
+        ProcessColumn::Pss => number(smaps.map(|smaps| smaps.pss as f64)),
+        ProcessColumn::Uss => number(smaps.map(|smaps| smaps.uss as f64)),
+        ProcessColumn::Container => SortValue::Text(workload_label(process, stats)),
+    }
+}
 
-        header_cells.push(header_with_indicator);
+/// Values the process is sorted by, computed once per refresh instead of once per comparison.
+fn sort_values(
+    process: &Process,
+    sort_by: &[(ProcessColumn, SortDirection)],
+    users: &UserNames,
+    stats: &ProcStatCache,
+    total_memory: f64,
+) -> Vec<SortValue> {
+    if sort_by.is_empty() {
+        let cpu_score = process.cpu_usage() as f64;
+        let mem_score = (process.memory() as f64 / total_memory) * 100.0;
+        return vec![SortValue::Number(cpu_score + mem_score)];
     }
 
-    let header = Row::new(header_cells).style(Style::default().fg(Color::Gray));
-    let rows: Vec<Row> = processes
+    sort_by
         .iter()
-        .skip(scroll_position)
-        .take(visible_lines)
+        .map(|(column, _)| sort_value(process, *column, users, stats))
+        .collect()
+}
+
+fn compare_sort_values(
+    a: &[SortValue],
+    b: &[SortValue],
+    sort_by: &[(ProcessColumn, SortDirection)],
+) -> Ordering {
+    // Without sort keys there's only the combined score, highest first
+    let directions = sort_by.iter().map(|(_, direction)| *direction);
+    let directions: Vec<SortDirection> = if sort_by.is_empty() {
+        vec![SortDirection::Descending]
+    } else {
+        directions.collect()
+    };
+
+    a.iter().zip(b).zip(directions).fold(
+        Ordering::Equal,
+        |ordering, ((a_value, b_value), direction)| {
+            ordering.then_with(|| {
+                direction.apply(a_value.partial_cmp(b_value).unwrap_or(Ordering::Equal))
+            })
+        },
+    )
+}
+
+/// UID to user name map. sysinfo reads the users only once, so they're read again and the map
+/// rebuilt whenever `/etc/passwd` changes, which catches renamed and replaced users too.
+#[derive(Default)]
+pub struct UserNames {
+    names: HashMap<Uid, String>,
+    loaded: bool,
+    passwd_modified: Option<SystemTime>,
+}
+
+impl UserNames {
+    /// Map of the users sysinfo already read, for one-off uses such as exports.
+    pub fn new(sys: &System) -> Self {
+        let mut users = Self::default();
+        users.rebuild(sys);
+        users
+    }
+
+    pub fn refresh(&mut self, sys: &mut System) {
+        let modified = fs::metadata("/etc/passwd")
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if self.loaded && modified == self.passwd_modified {
+            return;
+        }
+        self.loaded = true;
+        self.passwd_modified = modified;
+        sys.refresh_users_list();
+        self.rebuild(sys);
+    }
+
+    fn rebuild(&mut self, sys: &System) {
+        self.names = sys
+            .users()
+            .iter()
+            .map(|user| (user.id().clone(), user.name().to_string()))
+            .collect();
+    }
+
+    pub fn name(&self, process: &Process) -> &str {
+        process
+            .user_id()
+            .and_then(|id| self.names.get(id))
+            .map_or("unknown", String::as_str)
+    }
+}
+
+/// Full command line, kernel threads have none so they show their name.
+fn command_line(process: &Process) -> String {
+    if process.cmd().is_empty() {
+        process.name().to_string()
+    } else {
+        process.cmd().join(" ")
+    }
+}
+
+fn column_widths(columns: &[ProcessColumn], layout_width: usize) -> Vec<Constraint> {
+    // -2 for border, -1 for the scrollbar, plus the spacing between columns
+    let available = layout_width.saturating_sub(3 + columns.len().saturating_sub(1)) as u16;
+    let fixed: u16 = columns.iter().filter_map(|column| column.width()).sum();
+    let flexible = columns
+        .iter()
+        .filter(|column| column.width().is_none())
+        .count() as u16;
+    let flexible_width = (available.saturating_sub(fixed) / flexible.max(1)).max(10);
+
+    columns
+        .iter()
+        .map(|column| Constraint::Length(column.width().unwrap_or(flexible_width)))
+        .collect()
+}
+
+fn format_rate(bytes_per_second: f64) -> String {
+    if bytes_per_second >= 1024.0 * 1024.0 {
+        format!("{:.1} MB/s", bytes_per_second / 1024.0 / 1024.0)
+    } else if bytes_per_second >= 1024.0 {
+        format!("{:.1} KB/s", bytes_per_second / 1024.0)
+    } else {
+        format!("{:.0} B/s", bytes_per_second)
+    }
+}
+
+fn format_seconds(seconds: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 60 / 60,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn cell_text(
+    row: &ProcessRow,
+    column: ProcessColumn,
+    prefix_column: Option<ProcessColumn>,
+    users: &UserNames,
+    stats: &ProcStatCache,
+) -> String {
+    let process = row.process;
+    let stat = stats.stat(process.pid());
+    let io_rate = stats.io_rate(process.pid());
+    let smaps = stats.smaps(process.pid());
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    // The tree branches go in front of the first command column
+    let prefix = if prefix_column == Some(column) {
+        row.prefix.as_str()
+    } else {
+        ""
+    };
+
+    match column {
+        ProcessColumn::User => users.name(process).to_string(),
+        ProcessColumn::Pid => process.pid().to_string(),
+        ProcessColumn::Ppid => optional(process.parent().map(|ppid| ppid.to_string())),
+        ProcessColumn::Cpu => {
+            let cpu_usage = row
+                .subtree_usage
+                .map_or(process.cpu_usage(), |(cpu, _)| cpu);
+            format!("{:.1}", cpu_usage)
+        }
+        ProcessColumn::Memory => {
+            let memory = row
+                .subtree_usage
+                .map_or(process.memory(), |(_, memory)| memory);
+            format!("{}", memory / 1024 / 1024)
+        }
+        ProcessColumn::Time => format_seconds(process.run_time()),
+        ProcessColumn::Command => format!("{}{}", prefix, process.name()),
+        ProcessColumn::State => state_name(process.status()).to_string(),
+        ProcessColumn::Threads => optional(stat.map(|stat| stat.threads.to_string())),
+        ProcessColumn::Nice => optional(stat.map(|stat| stat.nice.to_string())),
+        ProcessColumn::Priority => optional(stat.map(|stat| stat.priority.to_string())),
+        ProcessColumn::VirtualMemory => format!("{}", process.virtual_memory() / 1024 / 1024),
+        ProcessColumn::Swap => optional(
+            stats
+                .swap(process.pid())
+                .map(|swap| format!("{}", swap / 1024 / 1024)),
+        ),
+        ProcessColumn::DiskRead => {
+            format!("{}", process.disk_usage().total_read_bytes / 1024 / 1024)
+        }
+        ProcessColumn::DiskWrite => {
+            format!("{}", process.disk_usage().total_written_bytes / 1024 / 1024)
+        }
+        ProcessColumn::StartTime => format_timestamp(process.start_time()),
+        ProcessColumn::CpuTime => optional(stat.map(|stat| format_seconds(stat.cpu_time() as u64))),
+        ProcessColumn::LastCpu => optional(stat.map(|stat| stat.last_cpu.to_string())),
+        ProcessColumn::Cmdline => format!("{}{}", prefix, command_line(process)),
+        ProcessColumn::ReadRate => optional(io_rate.map(|rate| format_rate(rate.read))),
+        ProcessColumn::WriteRate => optional(io_rate.map(|rate| format_rate(rate.write))),
+        ProcessColumn::IoRate => optional(io_rate.map(|rate| format_rate(rate.total()))),
+        ProcessColumn::Pss => optional(smaps.map(|smaps| format!("{}", smaps.pss / 1024 / 1024))),
+        ProcessColumn::Uss => optional(smaps.map(|smaps| format!("{}", smaps.uss / 1024 / 1024))),
+        ProcessColumn::Container => workload_label(process, stats),
+    }
+}
+
+fn build_flat_rows(processes: Vec<&Process>) -> Vec<ProcessRow<'_>> {
+    processes
+        .into_iter()
+        .map(|process| ProcessRow {
+            process,
+            prefix: String::new(),
+            subtree_usage: None,
+        })
+        .collect()
+}
+
+/// Orders the already sorted processes depth first, so siblings keep the sort order.
+fn build_tree_rows<'a>(
+    processes: Vec<&'a Process>,
+    tree_state: &ProcessTreeState,
+) -> Vec<ProcessRow<'a>> {
+    let pids: HashSet<Pid> = processes.iter().map(|process| process.pid()).collect();
+    let mut children: HashMap<Pid, Vec<&Process>> = HashMap::new();
+    let mut roots = Vec::new();
+    for process in &processes {
+        match process.parent() {
+            Some(parent) if pids.contains(&parent) && parent != process.pid() => {
+                children.entry(parent).or_default().push(process)
+            }
+            _ => roots.push(*process),
+        }
+    }
+
+    let mut subtree_usage = HashMap::new();
+    if tree_state.show_subtree_totals {
+        for root in &roots {
+            sum_subtree_usage(root, &children, &mut subtree_usage);
+        }
+    }
+
+    let mut rows = Vec::with_capacity(processes.len());
+    let mut visited = HashSet::new();
+    let roots_count = roots.len();
+    for (i, root) in roots.into_iter().enumerate() {
+        push_tree_rows(
+            root,
+            "",
+            i + 1 == roots_count,
+            true,
+            &children,
+            &subtree_usage,
+            tree_state,
+            &mut visited,
+            &mut rows,
+        );
+    }
+
+    rows
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_tree_rows<'a>(
+    process: &'a Process,
+    indent: &str,
+    is_last: bool,
+    is_root: bool,
+    children: &HashMap<Pid, Vec<&'a Process>>,
+    subtree_usage: &HashMap<Pid, (f32, u64)>,
+    tree_state: &ProcessTreeState,
+    visited: &mut HashSet<Pid>,
+    rows: &mut Vec<ProcessRow<'a>>,
+) {
+    // Guards against parent loops from processes that changed between refreshes
+    if !visited.insert(process.pid()) {
+        return;
+    }
+
+    let process_children = children.get(&process.pid());
+    let is_collapsed = tree_state.collapsed.contains(&process.pid());
+    let branch = match (is_root, is_last) {
+        (true, _) => "",
+        (false, true) => "└─",
+        (false, false) => "├─",
+    };
+    let marker = match (process_children.is_some(), is_collapsed) {
+        (false, _) => "",
+        (true, true) => "▸ ",
+        (true, false) => "▾ ",
+    };
+
+    rows.push(ProcessRow {
+        process,
+        prefix: format!("{}{}{}", indent, branch, marker),
+        subtree_usage: subtree_usage.get(&process.pid()).copied(),
+    });
+
+    let Some(process_children) = process_children else {
+        return;
+    };
+    if is_collapsed {
+        return;
+    }
+
+    let child_indent = match (is_root, is_last) {
+        (true, _) => indent.to_string(),
+        (false, true) => format!("{}  ", indent),
+        (false, false) => format!("{}│ ", indent),
+    };
+    for (i, child) in process_children.iter().enumerate() {
+        push_tree_rows(
+            child,
+            &child_indent,
+            i + 1 == process_children.len(),
+            false,
+            children,
+            subtree_usage,
+            tree_state,
+            visited,
+            rows,
+        );
+    }
+}
+
+fn sum_subtree_usage(
+    process: &Process,
+    children: &HashMap<Pid, Vec<&Process>>,
+    subtree_usage: &mut HashMap<Pid, (f32, u64)>,
+) -> (f32, u64) {
+    if let Some(usage) = subtree_usage.get(&process.pid()) {
+        return *usage;
+    }
+    // Placeholder so a parent loop can't recurse forever
+    subtree_usage.insert(process.pid(), (0.0, 0));
+
+    let mut usage = (process.cpu_usage(), process.memory());
+    for child in children.get(&process.pid()).into_iter().flatten() {
+        let (cpu, memory) = sum_subtree_usage(child, children, subtree_usage);
+        usage.0 += cpu;
+        usage.1 += memory;
+    }
+
+    subtree_usage.insert(process.pid(), usage);
+    usage
+}
+
+/// Columns without a group total sort the groups by their key.
+fn group_sort_value(group: &GroupRow, column: ProcessColumn) -> SortValue {
+    match column {
+        ProcessColumn::Cpu => SortValue::Number(group.cpu as f64),
+        ProcessColumn::Memory => SortValue::Number(group.memory as f64),
+        ProcessColumn::DiskRead => SortValue::Number(group.disk_read as f64),
+        ProcessColumn::DiskWrite => SortValue::Number(group.disk_written as f64),
+        ProcessColumn::ReadRate => SortValue::Number(group.read_rate),
+        ProcessColumn::WriteRate => SortValue::Number(group.write_rate),
+        ProcessColumn::IoRate => SortValue::Number(group.read_rate + group.write_rate),
+        _ => SortValue::Text(group.key.clone()),
+    }
+}
+
+fn compare_groups(
+    a: &GroupRow,
+    b: &GroupRow,
+    sort_by: &[(ProcessColumn, SortDirection)],
+    total_memory: f64,
+) -> Ordering {
+    let ordering = if sort_by.is_empty() {
+        // Same combined score as the processes
+        let a_combined = a.cpu as f64 + (a.memory as f64 / total_memory) * 100.0;
+        let b_combined = b.cpu as f64 + (b.memory as f64 / total_memory) * 100.0;
+        b_combined
+            .partial_cmp(&a_combined)
+            .unwrap_or(Ordering::Equal)
+    } else {
+        sort_by
+            .iter()
+            .fold(Ordering::Equal, |ordering, (column, direction)| {
+                ordering.then_with(|| {
+                    let a_value = group_sort_value(a, *column);
+                    let b_value = group_sort_value(b, *column);
+                    direction.apply(a_value.partial_cmp(&b_value).unwrap_or(Ordering::Equal))
+                })
+            })
+    };
+
+    ordering.then_with(|| a.key.cmp(&b.key))
+}
+
+/// Collapses the already sorted processes into groups, members of expanded groups follow their
+/// group row in the sort order.
+#[allow(clippy::too_many_arguments)]
+fn build_group_rows<'a>(
+    processes: Vec<&'a Process>,
+    grouping: ProcessGrouping,
+    group_state: &ProcessGroupState,
+    sort_by: &[(ProcessColumn, SortDirection)],
+    users: &UserNames,
+    stats: &ProcStatCache,
+    total_memory: f64,
+) -> Vec<TableRow<'a>> {
+    let mut groups: Vec<(GroupRow, Vec<&Process>)> = Vec::new();
+    let mut group_indexes: HashMap<String, usize> = HashMap::new();
+    for process in processes {
+        let key = group_key(process, grouping, users, stats);
+        let index = *group_indexes.entry(key.clone()).or_insert_with(|| {
+            groups.push((
+                GroupRow {
+                    expanded: group_state.expanded.contains(&key),
+                    key,
+                    count: 0,
+                    cpu: 0.0,
+                    memory: 0,
+                    read_rate: 0.0,
+                    write_rate: 0.0,
+                    disk_read: 0,
+                    disk_written: 0,
+                },
+                Vec::new(),
+            ));
+            groups.len() - 1
+        });
+
+        let (group, members) = &mut groups[index];
+        let io_rate = stats.io_rate(process.pid()).unwrap_or_default();
+        group.count += 1;
+        group.cpu += process.cpu_usage();
+        group.memory += process.memory();
+        group.read_rate += io_rate.read;
+        group.write_rate += io_rate.write;
+        group.disk_read += process.disk_usage().total_read_bytes;
+        group.disk_written += process.disk_usage().total_written_bytes;
+        members.push(process);
+    }
+
+    groups.sort_by(|(a, _), (b, _)| compare_groups(a, b, sort_by, total_memory));
+
+    let mut rows = Vec::new();
+    for (group, members) in groups {
+        let expanded = group.expanded;
+        rows.push(TableRow::Group(group));
+        if expanded {
+            rows.extend(members.into_iter().map(|process| {
+                TableRow::Process(ProcessRow {
+                    process,
+                    prefix: "  ".to_string(),
+                    subtree_usage: None,
+                })
+            }));
+        }
+    }
+    rows
+}
+
+fn group_cell_text(
+    group: &GroupRow,
+    column: ProcessColumn,
+    label_column: Option<ProcessColumn>,
+) -> String {
+    let mb = |bytes: u64| format!("{}", bytes / 1024 / 1024);
+
+    if label_column == Some(column) {
+        let marker = if group.expanded { "▾" } else { "▸" };
+        return format!("{} {} ({})", marker, group.key, group.count);
+    }
+    match column {
+        ProcessColumn::Cpu => format!("{:.1}", group.cpu),
+        ProcessColumn::Memory => mb(group.memory),
+        ProcessColumn::DiskRead => mb(group.disk_read),
+        ProcessColumn::DiskWrite => mb(group.disk_written),
+        ProcessColumn::ReadRate => format_rate(group.read_rate),
+        ProcessColumn::WriteRate => format_rate(group.write_rate),
+        ProcessColumn::IoRate => format_rate(group.read_rate + group.write_rate),
+        _ => String::new(),
+    }
+}
+
+fn processes_title(
+    sys: &System,
+    filter: &ProcessFilter,
+    grouping: Option<ProcessGrouping>,
+    shown_count: usize,
+    marked_count: usize,
+) -> String {
+    let grouping = grouping.map_or(String::new(), |grouping| {
+        format!(", grouped by {}", grouping.name())
+    });
+    let marked = if marked_count > 0 {
+        format!(", {} marked", marked_count)
+    } else {
+        String::new()
+    };
+    if !filter.is_active() {
+        return format!(
+            "Processes ({}){}{}",
+            sys.processes().len(),
+            grouping,
+            marked
+        );
+    }
+
+    let filter_text = match &filter.view {
+        Some(view) => format!("view: {}", view),
+        None => format!("filter: {}", filter.query),
+    };
+    let error = filter
+        .error
+        .as_ref()
+        .map_or(String::new(), |error| format!(" ({})", error));
+    format!(
+        "Processes ({}/{}){}{}, {}{}",
+        shown_count,
+        sys.processes().len(),
+        grouping,
+        marked,
+        filter_text,
+        error
+    )
+}
+
+/// One row of the processes table with its cells already formatted.
+struct ViewRow {
+    pid: Option<Pid>,
+    // Set on group summary rows, which have no PID
+    group: Option<String>,
+    marked: bool,
+    cells: Vec<String>,
+}
+
+/// Processes table contents, built once per refresh or change of the table settings. Draws only
+/// slice the visible window out of it.
+pub struct ProcessView {
+    columns: Vec<ProcessColumn>,
+    header: Vec<String>,
+    // Pinned rows come first and stay on screen while the rest scrolls
+    rows: Vec<ViewRow>,
+    pinned_count: usize,
+    title: String,
+}
+
+impl ProcessView {
+    pub fn empty() -> Self {
+        Self {
+            columns: Vec::new(),
+            header: Vec::new(),
+            rows: Vec::new(),
+            pinned_count: 0,
+            title: "Processes".to_string(),
+        }
+    }
+
+    /// Position of the selected row among the pinned rows, if it's one of them.
+    pub fn pinned_index(&self, selection: &ProcessSelection) -> Option<usize> {
+        (selection.index < self.pinned_count).then_some(selection.index)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn build_process_view(
+    sys: &System,
+    columns: &[ProcessColumn],
+    sort_by: &[(ProcessColumn, SortDirection)],
+    tree_state: &ProcessTreeState,
+    group_state: &ProcessGroupState,
+    filter: &ProcessFilter,
+    pins: &ProcessPins,
+    marks: &ProcessMarks,
+    stats: &ProcStatCache,
+    users: &UserNames,
+) -> ProcessView {
+    let total_memory = sys.total_memory() as f64;
+    let mut entries: Vec<_> = sys
+        .processes()
+        .values()
+        .filter(|process| filter.matches(process, users, stats))
         .map(|process| {
-            Row::new(vec![
-                process
-                    .user_id()
-                    .and_then(|id| sys.get_user_by_id(&id))
-                    .map(|user| user.name().to_string())
-                    .unwrap_or_else(|| "unknown".to_string()),
-                process.pid().to_string(),
-                process
-                    .parent()
-                    .map_or("-".to_string(), |ppid| ppid.to_string()),
-                format!("{:.1}", process.cpu_usage()),
-                format!("{}", process.memory() / 1024 / 1024),
-                format!(
-                    "{:02}:{:02}:{:02}",
-                    process.run_time() / 60 / 60,
-                    process.run_time() / 60 % 60,
-                    process.run_time() % 60
-                ),
-                process.name().to_string(),
-            ])
+            let values = sort_values(process, sort_by, users, stats, total_memory);
+            (process, values)
+        })
+        .collect();
+    let shown_count = entries.len();
+    // Ties are broken by PID, so equal rows don't swap places between refreshes
+    entries.sort_by(|(a, a_values), (b, b_values)| {
+        compare_sort_values(a_values, b_values, sort_by).then_with(|| a.pid().cmp(&b.pid()))
+    });
+    let processes: Vec<&Process> = entries.into_iter().map(|(process, _)| process).collect();
+
+    let table_rows = if let Some(grouping) = group_state.grouping {
+        build_group_rows(
+            processes,
+            grouping,
+            group_state,
+            sort_by,
+            users,
+            stats,
+            total_memory,
+        )
+    } else if tree_state.enabled {
+        build_tree_rows(processes, tree_state)
+            .into_iter()
+            .map(TableRow::Process)
+            .collect()
+    } else {
+        build_flat_rows(processes)
+            .into_iter()
+            .map(TableRow::Process)
+            .collect()
+    };
+
+    let mut header = Vec::new();
+    for &column in columns {
+        // Σ marks the columns that show subtree totals instead of the process' own usage
+        let header_text = match column {
+            ProcessColumn::Cpu | ProcessColumn::Memory
+                if tree_state.enabled && tree_state.show_subtree_totals =>
+            {
+                format!("{}Σ", column.title())
+            }
+            _ => column.title().to_string(),
+        };
+
+        // With several sort keys each one is numbered by its priority
+        let sort_key = sort_by
+            .iter()
+            .position(|(sort_column, _)| *sort_column == column);
+        let header_with_indicator = match sort_key {
+            Some(index) => {
+                let arrow = match sort_by[index].1 {
+                    SortDirection::Ascending => "↑",
+                    SortDirection::Descending => "↓",
+                };
+                let priority = if sort_by.len() > 1 {
+                    (index + 1).to_string()
+                } else {
+                    String::new()
+                };
+                format!("{}{}{}", header_text, arrow, priority)
+            }
+            None => header_text,
+        };
+
+        header.push(header_with_indicator);
+    }
+
+    let prefix_column = columns
+        .iter()
+        .find(|column| matches!(column, ProcessColumn::Command | ProcessColumn::Cmdline))
+        .copied();
+    // Group names go where the command is, or in the first column without one
+    let label_column = prefix_column.or(columns.first().copied());
+    let mut rows: Vec<ViewRow> = pins
+        .pinned
+        .iter()
+        .map(|pinned| match pinned.exited_at {
+            None => {
+                let row = sys.process(pinned.pid).map(|process| ProcessRow {
+                    process,
+                    prefix: String::new(),
+                    subtree_usage: None,
+                });
+                ViewRow {
+                    pid: Some(pinned.pid),
+                    group: None,
+                    marked: marks.is_marked(pinned.pid),
+                    cells: columns
+                        .iter()
+                        .map(|column| match &row {
+                            Some(row) => cell_text(row, *column, prefix_column, users, stats),
+                            None => "-".to_string(),
+                        })
+                        .collect(),
+                }
+            }
+            // Without a PID the row can't be the target of actions, which could hit a process
+            // reusing it
+            Some(exited_at) => ViewRow {
+                pid: None,
+                group: None,
+                marked: false,
+                cells: columns
+                    .iter()
+                    .map(|column| match column {
+                        ProcessColumn::Pid => pinned.pid.to_string(),
+                        column if Some(*column) == label_column => {
+                            format!("{} exited at {}", pinned.name, format_timestamp(exited_at))
+                        }
+                        _ => "-".to_string(),
+                    })
+                    .collect(),
+            },
+        })
+        .collect();
+    let pinned_count = rows.len();
+    // Pinned processes are listed above the rest whatever the filter. They still count towards
+    // their group and keep their children in the tree, only their own row below is left out
+    let pinned_pids = pins.live_pids();
+    let table_rows = table_rows.into_iter().filter(|row| match row {
+        TableRow::Process(row) => !pinned_pids.contains(&row.process.pid()),
+        TableRow::Group(_) => true,
+    });
+    rows.extend(table_rows.map(|row| {
+        match row {
+            TableRow::Process(row) => ViewRow {
+                pid: Some(row.process.pid()),
+                group: None,
+                marked: marks.is_marked(row.process.pid()),
+                cells: columns
+                    .iter()
+                    .map(|column| cell_text(&row, *column, prefix_column, users, stats))
+                    .collect(),
+            },
+            TableRow::Group(group) => ViewRow {
+                pid: None,
+                marked: false,
+                cells: columns
+                    .iter()
+                    .map(|column| group_cell_text(&group, *column, label_column))
+                    .collect(),
+                group: Some(group.key),
+            },
+        }
+    }));
+
+    ProcessView {
+        columns: columns.to_vec(),
+        header,
+        rows,
+        pinned_count,
+        title: processes_title(sys, filter, group_state.grouping, shown_count, marks.len()),
+    }
+}
+
+pub fn create_processes_table<'a>(
+    view: &'a ProcessView,
+    layout_width: usize,
+    layout_height: usize,
+    scroll_position: usize,
+    is_selected: bool,
+    selection: &mut ProcessSelection,
+) -> ProcessesTable<'a> {
+    // -2 for border, -1 for header
+    let visible_lines = layout_height.saturating_sub(3).max(1);
+    let highlight_style = get_highlight_style(is_selected);
+
+    // Pinned rows are drawn first, the rest scrolls in the lines left below them
+    let pinned_lines = view.pinned_count.min(visible_lines - 1);
+    let scrolled_lines = visible_lines - pinned_lines;
+    let max_scroll = (view.rows.len() - view.pinned_count).saturating_sub(scrolled_lines);
+    selection.resolve(&view.rows);
+    let scroll_position = selection
+        .scroll_position(scroll_position, scrolled_lines, view.pinned_count)
+        .min(max_scroll);
+    // Only the visible window is handed to the table, so the index is relative to it
+    let has_selection =
+        selection.index < view.pinned_count || selection.pid.is_some() || selection.group.is_some();
+    let table_index = match selection.index.checked_sub(view.pinned_count) {
+        Some(index) => pinned_lines + index.saturating_sub(scroll_position),
+        None => selection.index.min(pinned_lines.saturating_sub(1)),
+    };
+    selection
+        .table_state
+        .select(has_selection.then_some(table_index));
+
+    let header =
+        Row::new(view.header.iter().map(String::as_str)).style(Style::default().fg(Color::Gray));
+    let visible_rows = view.rows[..pinned_lines].iter().chain(
+        view.rows[view.pinned_count..]
+            .iter()
+            .skip(scroll_position)
+            .take(scrolled_lines),
+    );
+    let visible_pids = visible_rows.clone().filter_map(|row| row.pid).collect();
+    let rows: Vec<Row> = visible_rows
+        .enumerate()
+        .map(|(index, row)| {
+            let cells = Row::new(row.cells.iter().map(String::as_str));
+            if row.marked {
+                cells.style(
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else if index < pinned_lines {
+                cells.style(Style::default().fg(Color::Magenta))
+            } else if row.group.is_some() {
+                cells.style(Style::default().fg(Color::Yellow))
+            } else {
+                cells
+            }
         })
         .collect();
 
-    let all_lines_count = processes.len();
-    let max_scroll = all_lines_count.saturating_sub(visible_lines);
+    let widths = column_widths(&view.columns, layout_width);
     let table = Table::new(rows)
         .header(header)
         .block(
             Block::default()
-                .title(format!("Processes ({})", all_lines_count))
+                .title(view.title.as_str())
                 .title_style(highlight_style.title)
                 .borders(Borders::all())
                 .border_style(highlight_style.border)
                 .border_type(highlight_style.border_type),
         )
         .style(Style::default().fg(Color::Cyan))
-        .widths(&[
-            Constraint::Percentage(15), // User
-            Constraint::Percentage(10), // PID
-            Constraint::Percentage(10), // PPID
-            Constraint::Percentage(10), // CPU%
-            Constraint::Percentage(10), // MEM
-            Constraint::Percentage(15), // Time
-            Constraint::Percentage(40), // Command
-        ])
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )
         .column_spacing(1);
 
     ProcessesTable {
         chart: table,
+        widths,
         max_scroll,
+        scroll_position,
+        visible_pids,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Child, Command};
+
+    use super::*;
+
+    fn spawn_sleep() -> Child {
+        Command::new("sleep").arg("30").spawn().unwrap()
+    }
+
+    fn build_view(
+        sys: &System,
+        tree_state: &ProcessTreeState,
+        group_state: &ProcessGroupState,
+        pins: &ProcessPins,
+    ) -> ProcessView {
+        build_process_view(
+            sys,
+            &[ProcessColumn::Pid, ProcessColumn::Command],
+            &[],
+            tree_state,
+            group_state,
+            &ProcessFilter::new(),
+            pins,
+            &ProcessMarks::default(),
+            &ProcStatCache::default(),
+            &UserNames::default(),
+        )
+    }
+
+    #[test]
+    fn pinned_parent_keeps_its_children_in_the_tree() {
+        let mut child = spawn_sleep();
+        let mut sys = System::new();
+        sys.refresh_processes();
+        let parent_pid = Pid::from_u32(std::process::id());
+        let child_pid = Pid::from_u32(child.id());
+        let mut pins = ProcessPins::default();
+        pins.toggle(sys.process(parent_pid).unwrap());
+        let tree_state = ProcessTreeState {
+            enabled: true,
+            ..Default::default()
+        };
+
+        let view = build_view(&sys, &tree_state, &ProcessGroupState::default(), &pins);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        let parent_rows: Vec<_> = (0..view.rows.len())
+            .filter(|&index| view.rows[index].pid == Some(parent_pid))
+            .collect();
+        assert_eq!(parent_rows, [0]);
+        assert_eq!(view.pinned_count, 1);
+        // A re-rooted orphan would have no branch in front of its name
+        let child_row = view
+            .rows
+            .iter()
+            .find(|row| row.pid == Some(child_pid))
+            .unwrap();
+        assert!(child_row.cells[1].contains("└─"), "{}", child_row.cells[1]);
+    }
+
+    #[test]
+    fn pinned_member_counts_towards_its_group() {
+        let mut children = [spawn_sleep(), spawn_sleep()];
+        let mut sys = System::new();
+        sys.refresh_processes();
+        let mut pins = ProcessPins::default();
+        pins.toggle(sys.process(Pid::from_u32(children[0].id())).unwrap());
+        let group_state = ProcessGroupState {
+            grouping: Some(ProcessGrouping::Command),
+            expanded: HashSet::from(["sleep".to_string()]),
+        };
+
+        let view = build_view(&sys, &ProcessTreeState::default(), &group_state, &pins);
+        for child in &mut children {
+            child.kill().unwrap();
+            child.wait().unwrap();
+        }
+
+        let sleeping = sys
+            .processes()
+            .values()
+            .filter(|process| process.name() == "sleep")
+            .count();
+        let group_row = view
+            .rows
+            .iter()
+            .find(|row| row.group.as_deref() == Some("sleep"))
+            .unwrap();
+        assert_eq!(group_row.cells[1], format!("▾ sleep ({})", sleeping));
+        // The members listed below the group leave out the pinned one
+        let pinned_pid = Pid::from_u32(children[0].id());
+        let pinned_rows = view
+            .rows
+            .iter()
+            .filter(|row| row.pid == Some(pinned_pid))
+            .count();
+        assert_eq!(pinned_rows, 1);
+        assert_eq!(view.rows[0].pid, Some(pinned_pid));
     }
 }
@@ -0,0 +1,227 @@
+use std::{collections::HashMap, fs, time::Instant};
+
+use sysinfo::{Pid, ProcessExt, System, SystemExt};
+
+use crate::smaps::{SmapsRollup, SmapsSampler};
+
+/// Fields of `/proc/<pid>/stat` that sysinfo doesn't expose.
+#[derive(Clone, Copy, Default)]
+pub struct ProcStat {
+    // State letter, R, S, D, Z, T...
+    pub state: char,
+    pub priority: i64,
+    pub nice: i64,
+    pub threads: u64,
+    // Clock ticks spent in user and kernel mode
+    pub utime: u64,
+    pub stime: u64,
+    // CPU the process last ran on
+    pub last_cpu: u32,
+}
+
+impl ProcStat {
+    pub fn read(pid: Pid) -> Option<ProcStat> {
+        parse_stat(&fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+    }
+
+    /// Seconds spent in user and kernel mode.
+    pub fn cpu_time(&self) -> f64 {
+        (self.utime + self.stime) as f64 / clock_ticks_per_second()
+    }
+}
+
+/// Parses a stat line, it has the same layout for processes and for their threads.
+pub fn parse_stat(stat: &str) -> Option<ProcStat> {
+    // The command name may contain spaces and parentheses, the fields after it don't
+    let (_, fields) = stat.rsplit_once(')')?;
+    // Indexed from the state, which is field 3 in proc(5)
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+    let field = |number: usize| fields.get(number - 3).copied();
+
+    Some(ProcStat {
+        state: field(3)?.chars().next()?,
+        utime: field(14)?.parse().ok()?,
+        stime: field(15)?.parse().ok()?,
+        priority: field(18)?.parse().ok()?,
+        nice: field(19)?.parse().ok()?,
+        threads: field(20)?.parse().ok()?,
+        last_cpu: field(39)?.parse().ok()?,
+    })
+}
+
+/// Disk throughput of a process in bytes per second.
+#[derive(Clone, Copy, Default)]
+pub struct IoRate {
+    pub read: f64,
+    pub write: f64,
+}
+
+impl IoRate {
+    pub fn total(&self) -> f64 {
+        self.read + self.write
+    }
+}
+
+/// `/proc/<pid>/stat`, swap usage and cgroup of every process, read once per refresh and only
+/// when something on screen needs them, plus the disk IO rates between the last two refreshes.
+#[derive(Default)]
+pub struct ProcStatCache {
+    stats: HashMap<Pid, ProcStat>,
+    swap: HashMap<Pid, u64>,
+    cgroups: HashMap<Pid, String>,
+    smaps: HashMap<Pid, SmapsRollup>,
+    smaps_sampler: SmapsSampler,
+    io_rates: HashMap<Pid, IoRate>,
+    // Whether the cgroups were read, processes without one are missing from the map either way
+    cgroups_read: bool,
+    last_refresh: Option<Instant>,
+}
+
+impl ProcStatCache {
+    pub fn refresh(&mut self, sys: &System, read_stat: bool, read_swap: bool, read_cgroup: bool) {
+        self.stats.clear();
+        self.swap.clear();
+        self.cgroups.clear();
+        self.cgroups_read = read_cgroup;
+        self.io_rates.clear();
+
+        // sysinfo reports the bytes since its previous refresh, which is roughly our previous one
+        let now = Instant::now();
+        let seconds = self
+            .last_refresh
+            .map(|last_refresh| now.duration_since(last_refresh).as_secs_f64())
+            .filter(|seconds| *seconds > 0.0);
+        self.last_refresh = Some(now);
+
+        for (pid, process) in sys.processes() {
+            if let Some(seconds) = seconds {
+                let disk_usage = process.disk_usage();
+                self.io_rates.insert(
+                    *pid,
+                    IoRate {
+                        read: disk_usage.read_bytes as f64 / seconds,
+                        write: disk_usage.written_bytes as f64 / seconds,
+                    },
+                );
+            }
+
+            if read_stat {
+                if let Some(stat) = ProcStat::read(*pid) {
+                    self.stats.insert(*pid, stat);
+                }
+            }
+            if read_swap {
+                if let Some(swap) = read_swap_usage(*pid) {
+                    self.swap.insert(*pid, swap);
+                }
+            }
+            if read_cgroup {
+                if let Some(cgroup) = read_cgroup_path(*pid) {
+                    self.cgroups.insert(*pid, cgroup);
+                }
+            }
+        }
+    }
+
+    pub fn stat(&self, pid: Pid) -> Option<&ProcStat> {
+        self.stats.get(&pid)
+    }
+
+    pub fn io_rate(&self, pid: Pid) -> Option<IoRate> {
+        self.io_rates.get(&pid).copied()
+    }
+
+    /// Swapped out memory of the process in bytes.
+    pub fn swap(&self, pid: Pid) -> Option<u64> {
+        self.swap.get(&pid).copied()
+    }
+
+    pub fn cgroup(&self, pid: Pid) -> Option<&str> {
+        self.cgroups.get(&pid).map(String::as_str)
+    }
+
+    pub fn has_cgroups(&self) -> bool {
+        self.cgroups_read
+    }
+
+    /// Reads the cgroups ahead of the next refresh, for when something starts needing them.
+    pub fn refresh_cgroups(&mut self, sys: &System) {
+        self.cgroups = sys
+            .processes()
+            .keys()
+            .filter_map(|pid| Some((*pid, read_cgroup_path(*pid)?)))
+            .collect();
+        self.cgroups_read = true;
+    }
+
+    /// Takes the `smaps_rollup` values read in the background since the last refresh and asks
+    /// for the given processes to be read again. Processes never requested have no values.
+    pub fn refresh_smaps(&mut self, sys: &System, pids: Vec<Pid>) {
+        self.smaps = self.smaps_sampler.results(|pid| sys.process(pid).is_some());
+        self.smaps_sampler.request(pids);
+    }
+
+    /// Queues processes that just came into view, without waiting for the next refresh.
+    pub fn request_smaps(&mut self, pids: Vec<Pid>) {
+        self.smaps_sampler.request(pids);
+    }
+
+    pub fn clear_smaps(&mut self) {
+        self.smaps.clear();
+    }
+
+    pub fn smaps(&self, pid: Pid) -> Option<&SmapsRollup> {
+        self.smaps.get(&pid)
+    }
+}
+
+/// Path of the process' cgroup from `/proc/<pid>/cgroup`. The cgroup v2 entry is preferred, on
+/// v1-only systems, or hybrid ones where the process sits at the v2 root, the first hierarchy
+/// placing it deeper is used.
+pub fn read_cgroup_path(pid: Pid) -> Option<String> {
+    let content = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    // Lines are hierarchy-ID:controllers:path
+    let paths: Vec<(&str, &str)> = content
+        .lines()
+        .filter_map(|line| {
+            let (id, rest) = line.split_once(':')?;
+            let (_, path) = rest.split_once(':')?;
+            Some((id, path))
+        })
+        .collect();
+    paths
+        .iter()
+        .find(|(id, path)| *id == "0" && *path != "/")
+        .or(paths.iter().find(|(_, path)| *path != "/"))
+        .or(paths.first())
+        .map(|(_, path)| path.to_string())
+}
+
+/// VmSwap from `/proc/<pid>/status`, in bytes.
+fn read_swap_usage(pid: Pid) -> Option<u64> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let kb: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmSwap:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+/// Unit of the CPU times in `/proc`.
+#[cfg(unix)]
+pub fn clock_ticks_per_second() -> f64 {
+    // SAFETY: sysconf has no memory safety requirements
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        ticks if ticks > 0 => ticks as f64,
+        _ => 100.0,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn clock_ticks_per_second() -> f64 {
+    100.0
+}
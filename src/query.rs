@@ -0,0 +1,764 @@
+use std::fmt;
+
+use regex::{Regex, RegexBuilder};
+use sysinfo::{PidExt, Process, ProcessExt, ProcessStatus};
+
+use crate::{container::Workload, processes::UserNames, procstat::ProcStatCache};
+
+/// Parsed process filter expression, e.g. `cpu > 5 && user == "postgres"`.
+pub struct Query {
+    expr: Expr,
+}
+
+#[derive(Debug)]
+pub struct QueryError {
+    pub message: String,
+    // Character offset into the query where the problem starts
+    pub position: usize,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Operator, Value),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Cpu,
+    Mem,
+    Pid,
+    Ppid,
+    Time,
+    User,
+    Name,
+    Cmd,
+    State,
+    Container,
+}
+
+const FIELDS: [(&str, Field); 10] = [
+    ("cpu", Field::Cpu),
+    ("mem", Field::Mem),
+    ("pid", Field::Pid),
+    ("ppid", Field::Ppid),
+    ("time", Field::Time),
+    ("user", Field::User),
+    ("name", Field::Name),
+    ("cmd", Field::Cmd),
+    ("state", Field::State),
+    ("container", Field::Container),
+];
+
+impl Field {
+    fn name(self) -> &'static str {
+        FIELDS
+            .iter()
+            .find(|(_, field)| *field == self)
+            .map_or("", |(name, _)| name)
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Field::Cpu | Field::Mem | Field::Pid | Field::Ppid | Field::Time
+        )
+    }
+
+    /// Multiplier of a unit suffix, bare memory numbers are MB like in the table.
+    fn unit_multiplier(self, unit: &str) -> Option<f64> {
+        let unit = unit.to_lowercase();
+        match self {
+            Field::Cpu => matches!(unit.as_str(), "" | "%").then_some(1.0),
+            Field::Mem => match unit.as_str() {
+                "b" => Some(1.0),
+                "k" | "kb" => Some(1024.0),
+                "" | "m" | "mb" => Some(1024.0 * 1024.0),
+                "g" | "gb" => Some(1024.0 * 1024.0 * 1024.0),
+                "t" | "tb" => Some(1024.0 * 1024.0 * 1024.0 * 1024.0),
+                _ => None,
+            },
+            Field::Time => match unit.as_str() {
+                "" | "s" => Some(1.0),
+                "m" => Some(60.0),
+                "h" => Some(60.0 * 60.0),
+                "d" => Some(24.0 * 60.0 * 60.0),
+                _ => None,
+            },
+            _ => unit.is_empty().then_some(1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Match,
+    NotMatch,
+}
+
+enum Value {
+    Number(f64),
+    Text(String),
+    Pattern(Regex),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    // Value, unit suffix and the text as typed, e.g. 500, "MB" and "500MB". The value is None
+    // when the digits don't form a number, which is only an error when comparing to a number
+    Number(Option<f64>, String, String),
+    Text(String),
+    Operator(&'static str),
+    And,
+    Or,
+    Not,
+    LeftParen,
+    RightParen,
+    End,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Word(word) => format!("'{}'", word),
+            Token::Number(_, _, raw) => format!("'{}'", raw),
+            Token::Text(text) => format!("\"{}\"", text),
+            Token::Operator(operator) => format!("'{}'", operator),
+            Token::And => "'&&'".to_string(),
+            Token::Or => "'||'".to_string(),
+            Token::Not => "'!'".to_string(),
+            Token::LeftParen => "'('".to_string(),
+            Token::RightParen => "')'".to_string(),
+            Token::End => "end of query".to_string(),
+        }
+    }
+}
+
+fn error<T>(message: impl Into<String>, position: usize) -> Result<T, QueryError> {
+    Err(QueryError {
+        message: message.into(),
+        position,
+    })
+}
+
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let next = chars.get(i + 1).copied();
+
+        let token = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => Token::And,
+            ('|', Some('|')) => Token::Or,
+            ('=', Some('=')) => Token::Operator("=="),
+            ('!', Some('=')) => Token::Operator("!="),
+            ('!', Some('~')) => Token::Operator("!~"),
+            ('>', Some('=')) => Token::Operator(">="),
+            ('<', Some('=')) => Token::Operator("<="),
+            ('>', _) => Token::Operator(">"),
+            ('<', _) => Token::Operator("<"),
+            ('~', _) => Token::Operator("~"),
+            ('!', _) => Token::Not,
+            ('(', _) => Token::LeftParen,
+            (')', _) => Token::RightParen,
+            ('"' | '\'', _) => {
+                let quote = c;
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return error("unterminated string", start),
+                        Some(c) if *c == quote => break,
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(c) => {
+                            text.push(*c);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+                tokens.push((Token::Text(text), start));
+                continue;
+            }
+            (c, _) if c.is_ascii_digit() => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                let unit_start = i;
+                // Takes the rest of the word, so text like `1e3` or `10-rc1` stays in one token
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '%' | '_' | '-' | '.'))
+                {
+                    i += 1;
+                }
+                let unit: String = chars[unit_start..i].iter().collect();
+                let raw = chars[start..i].iter().collect();
+                tokens.push((Token::Number(number.parse().ok(), unit, raw), start));
+                continue;
+            }
+            (c, _) if c.is_alphanumeric() || c == '_' => {
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '-' | '.'))
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Word(chars[start..i].iter().collect()), start));
+                continue;
+            }
+            (c, _) => return error(format!("unexpected '{}'", c), start),
+        };
+
+        i += match token {
+            Token::And | Token::Or => 2,
+            Token::Operator(operator) => operator.len(),
+            _ => 1,
+        };
+        tokens.push((token, start));
+    }
+
+    tokens.push((Token::End, chars.len()));
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.index].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.index].1
+    }
+
+    fn advance(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.index].clone();
+        // End stays the current token once it's reached
+        if self.index + 1 < self.tokens.len() {
+            self.index += 1;
+        }
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_and()?;
+        while *self.peek() == Token::Or {
+            self.advance();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_unary()?;
+        while *self.peek() == Token::And {
+            self.advance();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        match self.peek() {
+            Token::Not => {
+                self.advance();
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Token::LeftParen => {
+                let (_, position) = self.advance();
+                let expr = self.parse_or()?;
+                match self.advance() {
+                    (Token::RightParen, _) => Ok(expr),
+                    (Token::End, _) => error("unclosed '('", position),
+                    (token, position) => error(
+                        format!("expected ')', found {}", token.describe()),
+                        position,
+                    ),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, QueryError> {
+        let field = match self.advance() {
+            (Token::Word(word), position) => {
+                let field = FIELDS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&word))
+                    .map(|(_, field)| *field);
+                match field {
+                    Some(field) => field,
+                    None => {
+                        let names: Vec<&str> = FIELDS.iter().map(|(name, _)| *name).collect();
+                        return error(
+                            format!(
+                                "unknown field '{}', expected one of {}",
+                                word,
+                                names.join(", ")
+                            ),
+                            position,
+                        );
+                    }
+                }
+            }
+            (token, position) => {
+                return error(
+                    format!("expected a field name, found {}", token.describe()),
+                    position,
+                )
+            }
+        };
+
+        let operator = match self.advance() {
+            (Token::Operator(operator), position) => {
+                let operator = match operator {
+                    "==" => Operator::Eq,
+                    "!=" => Operator::Ne,
+                    ">" => Operator::Gt,
+                    ">=" => Operator::Ge,
+                    "<" => Operator::Lt,
+                    "<=" => Operator::Le,
+                    "~" => Operator::Match,
+                    _ => Operator::NotMatch,
+                };
+                let is_pattern = matches!(operator, Operator::Match | Operator::NotMatch);
+                let is_ordering = matches!(
+                    operator,
+                    Operator::Gt | Operator::Ge | Operator::Lt | Operator::Le
+                );
+                if field.is_numeric() && is_pattern {
+                    return error("'~' only works on text fields", position);
+                }
+                if !field.is_numeric() && is_ordering {
+                    return error("text fields only support ==, !=, ~ and !~", position);
+                }
+                operator
+            }
+            (token, position) => {
+                return error(
+                    format!("expected an operator, found {}", token.describe()),
+                    position,
+                )
+            }
+        };
+
+        let (token, position) = self.advance();
+        let value = match (token, field.is_numeric()) {
+            (Token::Number(value, unit, raw), true) => {
+                let Some(value) = value else {
+                    let number = &raw[..raw.len() - unit.len()];
+                    return error(format!("invalid number '{}'", number), position);
+                };
+                match field.unit_multiplier(&unit) {
+                    Some(multiplier) => Value::Number(value * multiplier),
+                    None => return error(format!("unknown unit '{}'", unit), position),
+                }
+            }
+            // Text fields compare numbers as typed, e.g. a numeric user name such as 007
+            (Token::Word(text) | Token::Text(text) | Token::Number(_, _, text), false) => {
+                if matches!(operator, Operator::Match | Operator::NotMatch) {
+                    match RegexBuilder::new(&text).case_insensitive(true).build() {
+                        Ok(regex) => Value::Pattern(regex),
+                        Err(_) => return error("invalid regex", position),
+                    }
+                } else {
+                    Value::Text(text)
+                }
+            }
+            (Token::End, _) => return error("expected a value", position),
+            (token, true) => {
+                return error(
+                    format!(
+                        "'{}' needs a number, found {}",
+                        field.name(),
+                        token.describe()
+                    ),
+                    position,
+                )
+            }
+            (token, false) => {
+                return error(
+                    format!("expected a value, found {}", token.describe()),
+                    position,
+                )
+            }
+        };
+
+        Ok(Expr::Compare(field, operator, value))
+    }
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Query, QueryError> {
+        let mut parser = Parser {
+            tokens: tokenize(query)?,
+            index: 0,
+        };
+        if *parser.peek() == Token::End {
+            return error("empty query", 0);
+        }
+
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            Token::End => Ok(Query { expr }),
+            token => error(
+                format!("unexpected {}, expected '&&' or '||'", token.describe()),
+                parser.position(),
+            ),
+        }
+    }
+
+    /// Containers come from the cgroups in `stats`, which have to be read for `container`.
+    pub fn matches(&self, process: &Process, users: &UserNames, stats: &ProcStatCache) -> bool {
+        evaluate(&self.expr, process, users, stats)
+    }
+
+    /// Whether the query uses the `container` field.
+    pub fn needs_cgroup(&self) -> bool {
+        uses_field(&self.expr, Field::Container)
+    }
+}
+
+fn uses_field(expr: &Expr, field: Field) -> bool {
+    match expr {
+        Expr::And(left, right) | Expr::Or(left, right) => {
+            uses_field(left, field) || uses_field(right, field)
+        }
+        Expr::Not(expr) => uses_field(expr, field),
+        Expr::Compare(compared, _, _) => *compared == field,
+    }
+}
+
+fn evaluate(expr: &Expr, process: &Process, users: &UserNames, stats: &ProcStatCache) -> bool {
+    match expr {
+        Expr::And(left, right) => {
+            evaluate(left, process, users, stats) && evaluate(right, process, users, stats)
+        }
+        Expr::Or(left, right) => {
+            evaluate(left, process, users, stats) || evaluate(right, process, users, stats)
+        }
+        Expr::Not(expr) => !evaluate(expr, process, users, stats),
+        Expr::Compare(field, operator, value) => {
+            compare(*field, *operator, value, process, users, stats)
+        }
+    }
+}
+
+fn compare(
+    field: Field,
+    operator: Operator,
+    value: &Value,
+    process: &Process,
+    users: &UserNames,
+    stats: &ProcStatCache,
+) -> bool {
+    match value {
+        Value::Number(expected) => {
+            let actual = match field {
+                Field::Cpu => process.cpu_usage() as f64,
+                Field::Mem => process.memory() as f64,
+                Field::Pid => process.pid().as_u32() as f64,
+                Field::Ppid => process.parent().map_or(0.0, |ppid| ppid.as_u32() as f64),
+                Field::Time => process.run_time() as f64,
+                _ => return false,
+            };
+            match operator {
+                Operator::Eq => actual == *expected,
+                Operator::Ne => actual != *expected,
+                Operator::Gt => actual > *expected,
+                Operator::Ge => actual >= *expected,
+                Operator::Lt => actual < *expected,
+                Operator::Le => actual <= *expected,
+                Operator::Match | Operator::NotMatch => false,
+            }
+        }
+        Value::Text(expected) => {
+            let equal = text_field(field, process, users, stats).eq_ignore_ascii_case(expected);
+            match operator {
+                Operator::Ne => !equal,
+                _ => equal,
+            }
+        }
+        Value::Pattern(regex) => {
+            let found = regex.is_match(&text_field(field, process, users, stats));
+            match operator {
+                Operator::NotMatch => !found,
+                _ => found,
+            }
+        }
+    }
+}
+
+fn text_field(field: Field, process: &Process, users: &UserNames, stats: &ProcStatCache) -> String {
+    match field {
+        Field::User => users.name(process).to_string(),
+        Field::Name => process.name().to_string(),
+        // Kernel threads have no command line, their name stands in for it
+        Field::Cmd if process.cmd().is_empty() => process.name().to_string(),
+        Field::Cmd => process.cmd().join(" "),
+        Field::State => state_name(process.status()).to_string(),
+        Field::Container => stats
+            .cgroup(process.pid())
+            .and_then(Workload::from_cgroup_path)
+            .map(|workload| workload.label())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Short lowercase name of a process state, as used by `state == ...` queries.
+pub fn state_name(status: ProcessStatus) -> &'static str {
+    match status {
+        ProcessStatus::Idle => "idle",
+        ProcessStatus::Run => "running",
+        ProcessStatus::Sleep => "sleeping",
+        ProcessStatus::Stop => "stopped",
+        ProcessStatus::Zombie => "zombie",
+        ProcessStatus::Tracing => "tracing",
+        ProcessStatus::Dead => "dead",
+        ProcessStatus::Wakekill => "wakekill",
+        ProcessStatus::Waking => "waking",
+        ProcessStatus::Parked => "parked",
+        ProcessStatus::LockBlocked => "locked",
+        ProcessStatus::UninterruptibleDiskSleep => "disk-sleep",
+        ProcessStatus::Unknown(_) => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders the parsed expression with explicit grouping, e.g. `(cpu > 5 && !user == root)`.
+    fn describe(expr: &Expr) -> String {
+        match expr {
+            Expr::And(left, right) => format!("({} && {})", describe(left), describe(right)),
+            Expr::Or(left, right) => format!("({} || {})", describe(left), describe(right)),
+            Expr::Not(expr) => format!("!{}", describe(expr)),
+            Expr::Compare(field, operator, value) => {
+                let operator = match operator {
+                    Operator::Eq => "==",
+                    Operator::Ne => "!=",
+                    Operator::Gt => ">",
+                    Operator::Ge => ">=",
+                    Operator::Lt => "<",
+                    Operator::Le => "<=",
+                    Operator::Match => "~",
+                    Operator::NotMatch => "!~",
+                };
+                let value = match value {
+                    Value::Number(number) => number.to_string(),
+                    Value::Text(text) => text.clone(),
+                    Value::Pattern(regex) => format!("/{}/", regex.as_str()),
+                };
+                format!("{} {} {}", field.name(), operator, value)
+            }
+        }
+    }
+
+    fn parse(query: &str) -> String {
+        match Query::parse(query) {
+            Ok(query) => describe(&query.expr),
+            Err(err) => panic!("'{}' failed to parse: {}", query, err),
+        }
+    }
+
+    fn parse_error(query: &str) -> String {
+        match Query::parse(query) {
+            Ok(parsed) => panic!("'{}' parsed as {}", query, describe(&parsed.expr)),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_comparisons() {
+        assert_eq!(parse("cpu > 5"), "cpu > 5");
+        assert_eq!(parse("pid == 1"), "pid == 1");
+        assert_eq!(parse("ppid != 1"), "ppid != 1");
+        assert_eq!(parse("cpu >= 2.5"), "cpu >= 2.5");
+        assert_eq!(parse("time < 60"), "time < 60");
+        assert_eq!(parse("cpu <= 1"), "cpu <= 1");
+        assert_eq!(parse("user == \"postgres\""), "user == postgres");
+        assert_eq!(parse("user == 'postgres'"), "user == postgres");
+        assert_eq!(parse("name == nginx"), "name == nginx");
+        assert_eq!(parse("state == disk-sleep"), "state == disk-sleep");
+        assert_eq!(parse("cmd ~ \"java.*-Xmx\""), "cmd ~ /java.*-Xmx/");
+        assert_eq!(parse("container !~ '^docker:'"), "container !~ /^docker:/");
+    }
+
+    #[test]
+    fn parses_field_names_case_insensitively() {
+        assert_eq!(parse("CPU > 5"), "cpu > 5");
+    }
+
+    #[test]
+    fn keeps_escaped_quotes_in_strings() {
+        assert_eq!(parse(r#"cmd == "say \"hi\"""#), "cmd == say \"hi\"");
+    }
+
+    #[test]
+    fn compares_text_fields_to_numbers_as_text() {
+        assert_eq!(parse("user == 1000"), "user == 1000");
+        assert_eq!(parse("user == 007"), "user == 007");
+        assert_eq!(parse("name == 1e3"), "name == 1e3");
+        assert_eq!(parse("name == 1.2.3"), "name == 1.2.3");
+        assert_eq!(parse("cmd ~ 1.50"), "cmd ~ /1.50/");
+        assert_eq!(parse("state != 10-rc1"), "state != 10-rc1");
+    }
+
+    #[test]
+    fn applies_unit_suffixes() {
+        assert_eq!(parse("mem > 500"), "mem > 524288000");
+        assert_eq!(parse("mem > 500MB"), "mem > 524288000");
+        assert_eq!(parse("mem > 512b"), "mem > 512");
+        assert_eq!(parse("mem > 2KB"), "mem > 2048");
+        assert_eq!(parse("mem > 1k"), "mem > 1024");
+        assert_eq!(parse("mem > 1G"), "mem > 1073741824");
+        assert_eq!(parse("mem > 1tb"), "mem > 1099511627776");
+        assert_eq!(parse("cpu > 50%"), "cpu > 50");
+        assert_eq!(parse("time > 30s"), "time > 30");
+        assert_eq!(parse("time > 5m"), "time > 300");
+        assert_eq!(parse("time > 2h"), "time > 7200");
+        assert_eq!(parse("time > 1d"), "time > 86400");
+    }
+
+    #[test]
+    fn binds_and_tighter_than_or() {
+        assert_eq!(
+            parse("cpu > 1 || time > 1 && pid == 1"),
+            "(cpu > 1 || (time > 1 && pid == 1))"
+        );
+        assert_eq!(
+            parse("cpu > 1 && time > 1 || pid == 1"),
+            "((cpu > 1 && time > 1) || pid == 1)"
+        );
+    }
+
+    #[test]
+    fn groups_left_to_right() {
+        assert_eq!(
+            parse("cpu > 1 && time > 1 && pid == 1"),
+            "((cpu > 1 && time > 1) && pid == 1)"
+        );
+        assert_eq!(
+            parse("cpu > 1 || time > 1 || pid == 1"),
+            "((cpu > 1 || time > 1) || pid == 1)"
+        );
+    }
+
+    #[test]
+    fn binds_not_tightest_and_respects_parentheses() {
+        assert_eq!(parse("!cpu > 1 && time > 1"), "(!cpu > 1 && time > 1)");
+        assert_eq!(parse("!(cpu > 1 && time > 1)"), "!(cpu > 1 && time > 1)");
+        assert_eq!(
+            parse("(cpu > 1 || time > 1) && pid == 1"),
+            "((cpu > 1 || time > 1) && pid == 1)"
+        );
+        assert_eq!(parse("!!(pid == 1)"), "!!pid == 1");
+    }
+
+    #[test]
+    fn reports_errors_with_their_column() {
+        assert_eq!(parse_error(""), "empty query at column 1");
+        assert_eq!(parse_error("   "), "empty query at column 1");
+        assert_eq!(
+            parse_error("foo > 1"),
+            "unknown field 'foo', expected one of cpu, mem, pid, ppid, time, user, name, cmd, \
+             state, container at column 1"
+        );
+        assert_eq!(
+            parse_error("cpu 5"),
+            "expected an operator, found '5' at column 5"
+        );
+        assert_eq!(parse_error("cpu >"), "expected a value at column 6");
+        assert_eq!(
+            parse_error("cpu > high"),
+            "'cpu' needs a number, found 'high' at column 7"
+        );
+        assert_eq!(parse_error("mem > 5XB"), "unknown unit 'XB' at column 7");
+        assert_eq!(parse_error("time > 5%"), "unknown unit '%' at column 8");
+        assert_eq!(
+            parse_error("pid > 1.2.3"),
+            "invalid number '1.2.3' at column 7"
+        );
+        assert_eq!(
+            parse_error("cpu ~ 5"),
+            "'~' only works on text fields at column 5"
+        );
+        assert_eq!(
+            parse_error("user > root"),
+            "text fields only support ==, !=, ~ and !~ at column 6"
+        );
+        assert_eq!(parse_error("cmd ~ \"(\""), "invalid regex at column 7");
+        assert_eq!(
+            parse_error("user == \"root"),
+            "unterminated string at column 9"
+        );
+        assert_eq!(parse_error("cpu > 1 # x"), "unexpected '#' at column 9");
+    }
+
+    #[test]
+    fn reports_misplaced_operators_and_parentheses() {
+        assert_eq!(parse_error("(cpu > 1"), "unclosed '(' at column 1");
+        assert_eq!(
+            parse_error("(cpu > 1 pid == 1)"),
+            "expected ')', found 'pid' at column 10"
+        );
+        assert_eq!(
+            parse_error("cpu > 1)"),
+            "unexpected ')', expected '&&' or '||' at column 8"
+        );
+        assert_eq!(
+            parse_error("cpu > 1 pid == 1"),
+            "unexpected 'pid', expected '&&' or '||' at column 9"
+        );
+        assert_eq!(
+            parse_error("cpu > 1 &&"),
+            "expected a field name, found end of query at column 11"
+        );
+        assert_eq!(
+            parse_error("&& cpu > 1"),
+            "expected a field name, found '&&' at column 1"
+        );
+    }
+
+    #[test]
+    fn finds_container_comparisons() {
+        let needs_cgroup = |query: &str| Query::parse(query).unwrap().needs_cgroup();
+        assert!(needs_cgroup("container == nginx.service"));
+        assert!(needs_cgroup("cpu > 1 || !(container ~ docker)"));
+        assert!(!needs_cgroup("cpu > 1 && user == root"));
+    }
+}
@@ -0,0 +1,560 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+};
+use sysinfo::Pid;
+
+use crate::signals::describe_failures;
+
+/// Linux IO scheduling classes, as used by `ioprio_set`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum IoClass {
+    None,
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+impl IoClass {
+    fn from_raw(class: i64) -> IoClass {
+        match class {
+            1 => IoClass::Realtime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        }
+    }
+
+    fn raw(self) -> i64 {
+        match self {
+            IoClass::None => 0,
+            IoClass::Realtime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            IoClass::None => "none",
+            IoClass::Realtime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        }
+    }
+
+    fn next(self) -> IoClass {
+        IoClass::from_raw((self.raw() + 1) % 4)
+    }
+
+    fn prev(self) -> IoClass {
+        IoClass::from_raw((self.raw() + 3) % 4)
+    }
+
+    // Only realtime and best-effort take a priority level
+    fn has_priority(self) -> bool {
+        matches!(self, IoClass::Realtime | IoClass::BestEffort)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct SchedulingSettings {
+    nice: i32,
+    io_class: IoClass,
+    io_priority: u8,
+    affinity: Vec<bool>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SchedulingField {
+    Nice,
+    IoClass,
+    IoPriority,
+    Affinity,
+}
+
+const FIELDS: [SchedulingField; 4] = [
+    SchedulingField::Nice,
+    SchedulingField::IoClass,
+    SchedulingField::IoPriority,
+    SchedulingField::Affinity,
+];
+
+// CPUs per line in the affinity editor
+const AFFINITY_ROW_LENGTH: usize = 16;
+
+pub struct SchedulingDialog {
+    pids: Vec<Pid>,
+    // The process, or how many marked processes the settings are applied to
+    target: String,
+    title: String,
+    field: usize,
+    cpu_cursor: usize,
+    current: SchedulingSettings,
+    edited: SchedulingSettings,
+}
+
+pub enum SchedulingDialogAction {
+    None,
+    Close,
+    Apply,
+}
+
+impl SchedulingDialog {
+    /// Reads the current settings of the process, fails when they can't be read.
+    pub fn new(pid: Pid, name: String) -> Result<Self, String> {
+        let target = format!("{} ({})", name, pid);
+        Ok(Self {
+            pids: vec![pid],
+            title: format!("Scheduling of {}", target),
+            target,
+            ..Self::read(pid)?
+        })
+    }
+
+    /// Dialog applying the changed settings to every marked process. It starts from the
+    /// settings of the first one, `name` is its name.
+    pub fn for_marked(pids: Vec<Pid>, name: String) -> Result<Self, String> {
+        let first = *pids.first().ok_or("no process marked".to_string())?;
+        let target = format!("{} marked processes", pids.len());
+        Ok(Self {
+            title: format!("Scheduling of {}, showing {} ({})", target, name, first),
+            target,
+            pids,
+            ..Self::read(first)?
+        })
+    }
+
+    fn read(pid: Pid) -> Result<Self, String> {
+        let (io_class, io_priority) = read_io_priority(pid)?;
+        let current = SchedulingSettings {
+            nice: read_nice(pid)?,
+            io_class,
+            io_priority,
+            affinity: read_affinity(pid)?,
+        };
+
+        Ok(Self {
+            pids: Vec::new(),
+            target: String::new(),
+            title: String::new(),
+            field: 0,
+            cpu_cursor: 0,
+            edited: current.clone(),
+            current,
+        })
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> SchedulingDialogAction {
+        let field = FIELDS[self.field];
+        let settings = &mut self.edited;
+
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.field = (self.field + 1).min(FIELDS.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => self.field = self.field.saturating_sub(1),
+            KeyCode::Char('l') | KeyCode::Right => match field {
+                SchedulingField::Nice => settings.nice = (settings.nice + 1).min(19),
+                SchedulingField::IoClass => settings.io_class = settings.io_class.next(),
+                SchedulingField::IoPriority => {
+                    settings.io_priority = (settings.io_priority + 1).min(7)
+                }
+                SchedulingField::Affinity => {
+                    self.cpu_cursor = (self.cpu_cursor + 1).min(settings.affinity.len() - 1)
+                }
+            },
+            KeyCode::Char('h') | KeyCode::Left => match field {
+                SchedulingField::Nice => settings.nice = (settings.nice - 1).max(-20),
+                SchedulingField::IoClass => settings.io_class = settings.io_class.prev(),
+                SchedulingField::IoPriority => {
+                    settings.io_priority = settings.io_priority.saturating_sub(1)
+                }
+                SchedulingField::Affinity => self.cpu_cursor = self.cpu_cursor.saturating_sub(1),
+            },
+            KeyCode::Char(' ') if field == SchedulingField::Affinity => {
+                settings.affinity[self.cpu_cursor] = !settings.affinity[self.cpu_cursor];
+            }
+            KeyCode::Char('a') if field == SchedulingField::Affinity => {
+                settings.affinity.iter_mut().for_each(|cpu| *cpu = true);
+            }
+            KeyCode::Enter => return SchedulingDialogAction::Apply,
+            KeyCode::Esc | KeyCode::Char('q') => return SchedulingDialogAction::Close,
+            _ => {}
+        }
+        SchedulingDialogAction::None
+    }
+
+    /// Applies the fields that were changed and describes what was done.
+    pub fn apply(&self) -> Result<String, String> {
+        // Every edit is checked before anything is changed, so a rejected edit changes nothing
+        // Only the CPUs that were toggled are written, the rest of each process' mask is kept
+        let affinity_changes: Vec<(usize, bool)> = self
+            .edited
+            .affinity
+            .iter()
+            .zip(&self.current.affinity)
+            .enumerate()
+            .filter(|(_, (edited, current))| edited != current)
+            .map(|(cpu, (edited, _))| (cpu, *edited))
+            .collect();
+        let affinity_changed = !affinity_changes.is_empty();
+        if affinity_changed && !self.edited.affinity.contains(&true) {
+            return Err(format!(
+                "Failed to set CPU affinity of {}: no CPU selected",
+                self.target
+            ));
+        }
+
+        let mut changed = Vec::new();
+        let mut errors = Vec::new();
+
+        if self.edited.nice != self.current.nice {
+            changed.push("nice");
+            errors.extend(self.apply_each("nice", |pid| set_nice(pid, self.edited.nice)));
+        }
+        if self.edited.io_class != self.current.io_class
+            || (self.edited.io_class.has_priority()
+                && self.edited.io_priority != self.current.io_priority)
+        {
+            changed.push("IO priority");
+            errors.extend(self.apply_each("IO priority", |pid| {
+                set_io_priority(pid, self.edited.io_class, self.edited.io_priority)
+            }));
+        }
+        if affinity_changed {
+            changed.push("CPU affinity");
+            errors.extend(
+                self.apply_each("CPU affinity", |pid| set_affinity(pid, &affinity_changes)),
+            );
+        }
+
+        if !errors.is_empty() {
+            return Err(errors.join(" | "));
+        }
+        if changed.is_empty() {
+            return Ok(format!("No changes for {}", self.target));
+        }
+        Ok(format!("Updated {} of {}", changed.join(", "), self.target))
+    }
+
+    /// Sets one setting on every target, a failure doesn't stop the others from being changed.
+    /// Returns what failed and why.
+    fn apply_each(&self, setting: &str, set: impl Fn(Pid) -> Result<(), String>) -> Option<String> {
+        let failures: Vec<(Pid, String)> = self
+            .pids
+            .iter()
+            .filter_map(|pid| set(*pid).err().map(|err| (*pid, err)))
+            .collect();
+
+        match failures.as_slice() {
+            [] => None,
+            [(_, err)] if self.pids.len() == 1 => Some(format!(
+                "Failed to set {} of {}: {}",
+                setting, self.target, err
+            )),
+            _ => Some(format!(
+                "Failed to set {} of {} of {} processes: {}",
+                setting,
+                failures.len(),
+                self.pids.len(),
+                describe_failures(&failures)
+            )),
+        }
+    }
+}
+
+pub fn create_scheduling_dialog(dialog: &SchedulingDialog) -> Paragraph<'_> {
+    let selected = FIELDS[dialog.field];
+    let label_style = |field: SchedulingField| {
+        if field == selected {
+            Style::default()
+                .bg(Color::DarkGray)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        }
+    };
+    // Edited values show what they will replace
+    let current = |changed: bool, value: String| {
+        if changed {
+            format!("  (current: {})", value)
+        } else {
+            String::new()
+        }
+    };
+
+    let edited = &dialog.edited;
+    let io_priority = |settings: &SchedulingSettings| {
+        if settings.io_class.has_priority() {
+            settings.io_priority.to_string()
+        } else {
+            "-".to_string()
+        }
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(" Nice         ", label_style(SchedulingField::Nice)),
+            Span::raw(format!(
+                " ◀ {:>3} ▶{}",
+                edited.nice,
+                current(
+                    edited.nice != dialog.current.nice,
+                    dialog.current.nice.to_string()
+                )
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(" IO class     ", label_style(SchedulingField::IoClass)),
+            Span::raw(format!(
+                " ◀ {} ▶{}",
+                edited.io_class.name(),
+                current(
+                    edited.io_class != dialog.current.io_class,
+                    dialog.current.io_class.name().to_string()
+                )
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled(" IO priority  ", label_style(SchedulingField::IoPriority)),
+            Span::raw(format!(
+                " ◀ {} ▶{}",
+                io_priority(edited),
+                current(
+                    io_priority(edited) != io_priority(&dialog.current),
+                    io_priority(&dialog.current)
+                )
+            )),
+        ]),
+        Line::from(Span::styled(
+            " CPU affinity ",
+            label_style(SchedulingField::Affinity),
+        )),
+    ];
+
+    for (row, cpus) in edited.affinity.chunks(AFFINITY_ROW_LENGTH).enumerate() {
+        let mut spans = vec![Span::raw("  ")];
+        for (column, enabled) in cpus.iter().enumerate() {
+            let cpu = row * AFFINITY_ROW_LENGTH + column;
+            let mut style = if *enabled {
+                Style::default().fg(Color::Green)
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            if selected == SchedulingField::Affinity && cpu == dialog.cpu_cursor {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            spans.push(Span::styled(format!("{:>3}", cpu), style));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(
+        " j/k: Field | h/l: Change | Space: Toggle CPU | a: All CPUs",
+    ));
+    lines.push(Line::from(" Enter: Apply | Esc: Cancel"));
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(dialog.title.as_str())
+            .borders(Borders::all())
+            .style(Style::default().fg(Color::Yellow)),
+    )
+}
+
+/// Height the dialog needs to show every CPU of the affinity mask.
+pub fn scheduling_dialog_height(dialog: &SchedulingDialog) -> u16 {
+    let affinity_rows = dialog.edited.affinity.len().div_ceil(AFFINITY_ROW_LENGTH);
+    (affinity_rows + 9) as u16
+}
+
+#[cfg(target_os = "linux")]
+fn read_nice(pid: Pid) -> Result<i32, String> {
+    crate::procstat::ProcStat::read(pid)
+        .map(|stat| stat.nice as i32)
+        .ok_or("can't read /proc stat".to_string())
+}
+
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_SHIFT: i64 = 13;
+
+#[cfg(target_os = "linux")]
+fn read_io_priority(pid: Pid) -> Result<(IoClass, u8), String> {
+    use sysinfo::PidExt;
+
+    // SAFETY: ioprio_get only takes integers, failures are reported through errno
+    let ioprio = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_get,
+            IOPRIO_WHO_PROCESS,
+            pid.as_u32() as libc::c_long,
+        )
+    };
+    if ioprio < 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+
+    let ioprio = ioprio as i64;
+    let class = IoClass::from_raw(ioprio >> IOPRIO_CLASS_SHIFT);
+    // Without a class the kernel derives the level from nice, best-effort 4 is what nice 0 maps to
+    let level = if class.has_priority() {
+        (ioprio & 0xff) as u8
+    } else {
+        4
+    };
+    Ok((class, level))
+}
+
+#[cfg(target_os = "linux")]
+fn get_affinity(pid: Pid) -> Result<libc::cpu_set_t, String> {
+    use sysinfo::PidExt;
+
+    // SAFETY: cpu_set_t is plain data, zeroed is an empty set
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    // SAFETY: the size passed matches the set the kernel writes into
+    let result = unsafe {
+        libc::sched_getaffinity(
+            pid.as_u32() as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &mut set,
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(set)
+}
+
+/// Number of CPUs the affinity editor lists. Offline CPUs are included, as a mask may name any
+/// possible CPU, but never more than a cpu_set_t holds.
+#[cfg(target_os = "linux")]
+fn affinity_cpu_count(set: &libc::cpu_set_t) -> usize {
+    let set_size = libc::CPU_SETSIZE as usize;
+    let possible = std::fs::read_to_string("/sys/devices/system/cpu/possible")
+        .ok()
+        .and_then(|list| crate::cgroup::parse_cpu_list(&list))
+        .and_then(|cpus| cpus.into_iter().max())
+        .map(|last| last + 1);
+    // Without sysfs the highest CPU in the mask has to do
+    let count = possible.unwrap_or_else(|| {
+        (0..set_size)
+            .rev()
+            // SAFETY: CPU_ISSET only reads the set and every CPU is below CPU_SETSIZE
+            .find(|&cpu| unsafe { libc::CPU_ISSET(cpu, set) })
+            .map_or(1, |cpu| cpu + 1)
+    });
+    count.min(set_size)
+}
+
+#[cfg(target_os = "linux")]
+fn read_affinity(pid: Pid) -> Result<Vec<bool>, String> {
+    let set = get_affinity(pid)?;
+    // SAFETY: CPU_ISSET only reads the set and the count is capped at CPU_SETSIZE
+    Ok((0..affinity_cpu_count(&set))
+        .map(|cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn set_nice(pid: Pid, nice: i32) -> Result<(), String> {
+    use sysinfo::PidExt;
+
+    // SAFETY: setpriority only takes integers, failures are reported through errno
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid.as_u32() as libc::id_t, nice) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_io_priority(pid: Pid, class: IoClass, level: u8) -> Result<(), String> {
+    use sysinfo::PidExt;
+
+    let level = if class.has_priority() { level } else { 0 };
+    let ioprio = (class.raw() << IOPRIO_CLASS_SHIFT) | level as i64;
+    // SAFETY: ioprio_set only takes integers, failures are reported through errno
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            pid.as_u32() as libc::c_long,
+            ioprio as libc::c_long,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+/// Sets or clears the given CPUs in the process' mask and leaves the others as they are.
+#[cfg(target_os = "linux")]
+fn set_affinity(pid: Pid, changes: &[(usize, bool)]) -> Result<(), String> {
+    use sysinfo::PidExt;
+
+    let mut set = get_affinity(pid)?;
+    let set_size = libc::CPU_SETSIZE as usize;
+    for &(cpu, enabled) in changes.iter().filter(|(cpu, _)| *cpu < set_size) {
+        // SAFETY: CPU_SET and CPU_CLR panic past CPU_SETSIZE, which is filtered out above
+        unsafe {
+            if enabled {
+                libc::CPU_SET(cpu, &mut set);
+            } else {
+                libc::CPU_CLR(cpu, &mut set);
+            }
+        }
+    }
+
+    // SAFETY: the size passed matches the set the kernel reads from
+    let result = unsafe {
+        libc::sched_setaffinity(
+            pid.as_u32() as libc::pid_t,
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_nice(_pid: Pid) -> Result<i32, String> {
+    Err("scheduling controls are only supported on Linux".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_io_priority(_pid: Pid) -> Result<(IoClass, u8), String> {
+    Err("scheduling controls are only supported on Linux".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_affinity(_pid: Pid) -> Result<Vec<bool>, String> {
+    Err("scheduling controls are only supported on Linux".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_nice(_pid: Pid, _nice: i32) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_io_priority(_pid: Pid, _class: IoClass, _level: u8) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_pid: Pid, _changes: &[(usize, bool)]) -> Result<(), String> {
+    Err("not supported on this platform".to_string())
+}
@@ -0,0 +1,271 @@
+use std::collections::{HashMap, HashSet};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph},
+};
+use sysinfo::{Pid, ProcessExt, Signal, System, SystemExt};
+
+/// Signals offered by the dialog, with their conventional names.
+const SIGNALS: [(Signal, &str); 8] = [
+    (Signal::Term, "TERM"),
+    (Signal::Kill, "KILL"),
+    (Signal::Hangup, "HUP"),
+    (Signal::Interrupt, "INT"),
+    (Signal::Stop, "STOP"),
+    (Signal::Continue, "CONT"),
+    (Signal::User1, "USR1"),
+    (Signal::User2, "USR2"),
+];
+
+pub struct SignalDialog {
+    pids: Vec<Pid>,
+    // The process, or how many marked processes get the signal
+    target: String,
+    selected: usize,
+    tree: bool,
+    confirming: bool,
+}
+
+pub enum SignalDialogAction {
+    None,
+    Close,
+    Send {
+        pids: Vec<Pid>,
+        signal: Signal,
+        signal_name: &'static str,
+        tree: bool,
+    },
+}
+
+impl SignalDialog {
+    pub fn new(pid: Pid, name: String) -> Self {
+        Self {
+            pids: vec![pid],
+            target: format!("{} ({})", name, pid),
+            selected: 0,
+            tree: false,
+            confirming: false,
+        }
+    }
+
+    /// Dialog sending the signal to every marked process.
+    pub fn for_marked(pids: Vec<Pid>) -> Self {
+        Self {
+            target: format!("{} marked processes", pids.len()),
+            pids,
+            selected: 0,
+            tree: false,
+            confirming: false,
+        }
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> SignalDialogAction {
+        if self.confirming {
+            return match code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    let (signal, signal_name) = SIGNALS[self.selected];
+                    SignalDialogAction::Send {
+                        pids: self.pids.clone(),
+                        signal,
+                        signal_name,
+                        tree: self.tree,
+                    }
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.confirming = false;
+                    SignalDialogAction::None
+                }
+                _ => SignalDialogAction::None,
+            };
+        }
+
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = (self.selected + 1).min(SIGNALS.len() - 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            KeyCode::Char('t') => self.tree = !self.tree,
+            KeyCode::Enter => self.confirming = true,
+            KeyCode::Esc | KeyCode::Char('q') => return SignalDialogAction::Close,
+            _ => {}
+        }
+        SignalDialogAction::None
+    }
+}
+
+pub fn create_signal_dialog(dialog: &SignalDialog) -> Paragraph<'_> {
+    let mut lines: Vec<Line> = SIGNALS
+        .iter()
+        .enumerate()
+        .map(|(i, (_, signal_name))| {
+            let style = if i == dialog.selected {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::styled(format!(" SIG{:<6}", signal_name), style)
+        })
+        .collect();
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(format!(
+        " t: Kill process tree [{}]",
+        if dialog.tree { "x" } else { " " }
+    )));
+    lines.push(Line::from(""));
+
+    if dialog.confirming {
+        let (_, signal_name) = SIGNALS[dialog.selected];
+        let descendants = match (dialog.tree, dialog.pids.len()) {
+            (false, _) => "",
+            (true, 1) => " and all of its descendants",
+            (true, _) => " and all of their descendants",
+        };
+        lines.push(Line::styled(
+            format!(
+                " Send SIG{} to {}{}? y/n",
+                signal_name, dialog.target, descendants
+            ),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    } else {
+        lines.push(Line::from(" j/k: Select | Enter: Send | Esc: Cancel"));
+    }
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .title(format!("Send signal to {}", dialog.target))
+            .borders(Borders::all())
+            .style(Style::default().fg(Color::Yellow)),
+    )
+}
+
+/// Sends the signal to the processes, and first to all of their descendants when `tree` is set.
+/// A failure doesn't stop the others from getting the signal. Returns how many processes got it,
+/// or what failed and why.
+pub fn send_signal(
+    sys: &System,
+    pids: &[Pid],
+    signal: Signal,
+    tree: bool,
+) -> Result<usize, String> {
+    let mut targets = Vec::new();
+    let mut seen = HashSet::new();
+    for pid in pids {
+        let mut process_targets = if tree {
+            descendants(sys, *pid)
+        } else {
+            Vec::new()
+        };
+        // Children go first so a parent can't respawn them before it gets the signal itself
+        process_targets.reverse();
+        process_targets.push(*pid);
+        // Marked processes can be descendants of each other, each one is signalled once
+        targets.extend(process_targets.into_iter().filter(|pid| seen.insert(*pid)));
+    }
+
+    let failures: Vec<(Pid, String)> = targets
+        .iter()
+        .filter_map(|target| kill(sys, *target, signal).err().map(|err| (*target, err)))
+        .collect();
+
+    match failures.as_slice() {
+        [] => Ok(targets.len()),
+        [(target, err)] if targets.len() == 1 => {
+            let name = sys
+                .process(*target)
+                .map_or("?".to_string(), |process| process.name().to_string());
+            Err(format!("Failed to signal {} ({}): {}", name, target, err))
+        }
+        _ => Err(format!(
+            "Signalled {} of {} processes, {} failed: {}",
+            targets.len() - failures.len(),
+            targets.len(),
+            failures.len(),
+            describe_failures(&failures)
+        )),
+    }
+}
+
+/// Lists failed processes by error, such as `No such process: 12, 34; Permission denied: 56`.
+pub fn describe_failures(failures: &[(Pid, String)]) -> String {
+    let mut errors: Vec<(&str, Vec<String>)> = Vec::new();
+    for (pid, err) in failures {
+        match errors.iter_mut().find(|(error, _)| error == err) {
+            Some((_, pids)) => pids.push(pid.to_string()),
+            None => errors.push((err, vec![pid.to_string()])),
+        }
+    }
+    errors
+        .iter()
+        .map(|(error, pids)| format!("{}: {}", error, pids.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// All processes below `pid`, parents before their children.
+fn descendants(sys: &System, pid: Pid) -> Vec<Pid> {
+    let mut children: HashMap<Pid, Vec<Pid>> = HashMap::new();
+    for process in sys.processes().values() {
+        if let Some(parent) = process.parent() {
+            children.entry(parent).or_default().push(process.pid());
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut visited = HashSet::from([pid]);
+    let mut index = 0;
+    let mut parents = vec![pid];
+    while index < parents.len() {
+        for child in children.get(&parents[index]).into_iter().flatten() {
+            if visited.insert(*child) {
+                parents.push(*child);
+                result.push(*child);
+            }
+        }
+        index += 1;
+    }
+    result
+}
+
+#[cfg(unix)]
+fn kill(_sys: &System, pid: Pid, signal: Signal) -> Result<(), String> {
+    use sysinfo::PidExt;
+
+    let signal = match signal {
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+        Signal::Hangup => libc::SIGHUP,
+        Signal::Interrupt => libc::SIGINT,
+        Signal::Stop => libc::SIGSTOP,
+        Signal::Continue => libc::SIGCONT,
+        Signal::User1 => libc::SIGUSR1,
+        Signal::User2 => libc::SIGUSR2,
+        _ => return Err("unsupported signal".to_string()),
+    };
+
+    // SAFETY: kill has no memory safety requirements, failures are reported through errno
+    if unsafe { libc::kill(pid.as_u32() as libc::pid_t, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error().to_string())
+    }
+}
+
+#[cfg(not(unix))]
+fn kill(sys: &System, pid: Pid, signal: Signal) -> Result<(), String> {
+    match sys.process(pid).map(|process| process.kill_with(signal)) {
+        Some(Some(true)) => Ok(()),
+        Some(Some(false)) => Err("permission denied or process exited".to_string()),
+        Some(None) => Err("signal not supported on this platform".to_string()),
+        None => Err("process exited".to_string()),
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    collections::HashMap,
+    fs,
+    sync::{
+        mpsc::{sync_channel, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use sysinfo::Pid;
+
+/// Memory totals of a process from `/proc/<pid>/smaps_rollup`, in bytes.
+#[derive(Clone, Copy, Default)]
+pub struct SmapsRollup {
+    pub rss: u64,
+    // Proportional set size, shared pages are split between the processes mapping them
+    pub pss: u64,
+    // Unique set size, the pages only this process maps
+    pub uss: u64,
+    pub swap: u64,
+}
+
+impl SmapsRollup {
+    pub fn read(pid: Pid) -> Option<SmapsRollup> {
+        let content = fs::read_to_string(format!("/proc/{}/smaps_rollup", pid)).ok()?;
+        let mut rollup = SmapsRollup::default();
+        for (name, bytes) in content.lines().filter_map(parse_size_line) {
+            match name {
+                "Rss" => rollup.rss = bytes,
+                "Pss" => rollup.pss = bytes,
+                "Private_Clean" | "Private_Dirty" => rollup.uss += bytes,
+                "Swap" => rollup.swap = bytes,
+                _ => {}
+            }
+        }
+        Some(rollup)
+    }
+}
+
+/// Parses `Name:   1234 kB` lines, the size is returned in bytes.
+fn parse_size_line(line: &str) -> Option<(&str, u64)> {
+    let (name, value) = line.split_once(':')?;
+    let kb: u64 = value.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some((name, kb * 1024))
+}
+
+/// Reads `smaps_rollup` on a background thread. The kernel walks the page tables of the process
+/// to produce it, which is too slow to do for every process on each refresh, so only the
+/// requested PIDs are read and their last values are kept.
+#[derive(Default)]
+pub struct SmapsSampler {
+    requests: Option<SyncSender<Vec<Pid>>>,
+    results: Arc<Mutex<HashMap<Pid, SmapsRollup>>>,
+}
+
+impl SmapsSampler {
+    /// Queues the PIDs to be read, dropped if the previous request is still being read.
+    pub fn request(&mut self, pids: Vec<Pid>) {
+        let requests = self.requests.get_or_insert_with(|| {
+            let (sender, receiver) = sync_channel::<Vec<Pid>>(1);
+            let results = Arc::clone(&self.results);
+            thread::spawn(move || {
+                for pids in receiver {
+                    for pid in pids {
+                        let rollup = SmapsRollup::read(pid);
+                        let mut results = results.lock().unwrap_or_else(|err| err.into_inner());
+                        match rollup {
+                            Some(rollup) => results.insert(pid, rollup),
+                            None => results.remove(&pid),
+                        };
+                    }
+                }
+            });
+            sender
+        });
+        let _ = requests.try_send(pids);
+    }
+
+    /// Values read so far, forgetting the processes that are gone.
+    pub fn results(&self, is_alive: impl Fn(Pid) -> bool) -> HashMap<Pid, SmapsRollup> {
+        let mut results = self.results.lock().unwrap_or_else(|err| err.into_inner());
+        results.retain(|pid, _| is_alive(*pid));
+        results.clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MappingKind {
+    Heap,
+    Stack,
+    Anonymous,
+    File,
+    Other,
+}
+
+impl MappingKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            MappingKind::Heap => "heap",
+            MappingKind::Stack => "stack",
+            MappingKind::Anonymous => "anon",
+            MappingKind::File => "file-backed",
+            MappingKind::Other => "other",
+        }
+    }
+
+    fn from_path(path: &str) -> MappingKind {
+        if path.is_empty() || path.starts_with("[anon") {
+            MappingKind::Anonymous
+        } else if path == "[heap]" {
+            MappingKind::Heap
+        } else if path.starts_with("[stack") {
+            MappingKind::Stack
+        } else if path.starts_with('/') {
+            MappingKind::File
+        } else {
+            // [vdso], [vvar], [vsyscall]...
+            MappingKind::Other
+        }
+    }
+}
+
+/// Totals of the mappings of one kind, sizes in bytes.
+pub struct MappingUsage {
+    pub kind: MappingKind,
+    pub count: usize,
+    pub size: u64,
+    pub rss: u64,
+    pub pss: u64,
+    pub swap: u64,
+}
+
+/// Sums `/proc/<pid>/smaps` by kind of mapping, kinds without mappings are left out.
+pub fn read_mapping_breakdown(pid: Pid) -> Vec<MappingUsage> {
+    let Ok(content) = fs::read_to_string(format!("/proc/{}/smaps", pid)) else {
+        return Vec::new();
+    };
+
+    let mut usage: Vec<MappingUsage> = [
+        MappingKind::Heap,
+        MappingKind::Stack,
+        MappingKind::Anonymous,
+        MappingKind::File,
+        MappingKind::Other,
+    ]
+    .into_iter()
+    .map(|kind| MappingUsage {
+        kind,
+        count: 0,
+        size: 0,
+        rss: 0,
+        pss: 0,
+        swap: 0,
+    })
+    .collect();
+
+    let mut current: Option<usize> = None;
+    for line in content.lines() {
+        if let Some((name, bytes)) = parse_size_line(line) {
+            let Some(entry) = current.map(|index| &mut usage[index]) else {
+                continue;
+            };
+            match name {
+                "Size" => entry.size += bytes,
+                "Rss" => entry.rss += bytes,
+                "Pss" => entry.pss += bytes,
+                "Swap" => entry.swap += bytes,
+                _ => {}
+            }
+            continue;
+        }
+
+        // Mapping headers are `address perms offset dev inode [path]`, the path may have spaces
+        let fields: Vec<&str> = line.splitn(6, ' ').collect();
+        if fields.len() >= 5 && fields[0].contains('-') {
+            let path = fields.get(5).map_or("", |path| path.trim());
+            let kind = MappingKind::from_path(path);
+            let index = usage.iter().position(|entry| entry.kind == kind);
+            if let Some(index) = index {
+                usage[index].count += 1;
+            }
+            current = index;
+        }
+    }
+
+    usage.retain(|entry| entry.count > 0);
+    usage
+}
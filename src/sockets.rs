@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+/// One entry of the socket tables in `/proc/net`.
+#[derive(Clone)]
+pub struct Socket {
+    pub protocol: &'static str,
+    pub local: String,
+    // Empty for unix sockets and for sockets that aren't connected
+    pub remote: String,
+    pub state: &'static str,
+    // Bytes waiting to be sent and read, always 0 for unix sockets. For listening TCP sockets
+    // the receive queue is the number of connections waiting to be accepted.
+    pub tx_queue: u64,
+    pub rx_queue: u64,
+    pub inode: u64,
+}
+
+/// Reads the TCP, UDP and unix socket tables of a network namespace, `net_dir` is `/proc/net`
+/// or `/proc/<pid>/net` for the namespace of a process.
+pub fn read_sockets(net_dir: &str) -> Vec<Socket> {
+    let mut sockets = Vec::new();
+    for (file, protocol) in [
+        ("tcp", "tcp"),
+        ("tcp6", "tcp6"),
+        ("udp", "udp"),
+        ("udp6", "udp6"),
+    ] {
+        if let Ok(content) = fs::read_to_string(format!("{}/{}", net_dir, file)) {
+            sockets.extend(
+                content
+                    .lines()
+                    .skip(1) // header
+                    .filter_map(|line| parse_inet_line(line, protocol)),
+            );
+        }
+    }
+    if let Ok(content) = fs::read_to_string(format!("{}/unix", net_dir)) {
+        sockets.extend(content.lines().skip(1).filter_map(parse_unix_line));
+    }
+    sockets
+}
+
+/// Sockets by inode, which is what `/proc/<pid>/fd` links point at.
+pub fn sockets_by_inode(net_dir: &str) -> HashMap<u64, Socket> {
+    read_sockets(net_dir)
+        .into_iter()
+        .map(|socket| (socket.inode, socket))
+        .collect()
+}
+
+/// Port of an address formatted by `read_sockets`, None for unix socket paths.
+pub fn address_port(address: &str) -> Option<u16> {
+    address.rsplit_once(':')?.1.parse().ok()
+}
+
+// sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode ...
+fn parse_inet_line(line: &str, protocol: &'static str) -> Option<Socket> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let state = u8::from_str_radix(fields.get(3)?, 16).ok()?;
+    let is_tcp = protocol.starts_with("tcp");
+    let remote = parse_address(fields.get(2)?)?;
+    let (tx_queue, rx_queue) = fields.get(4)?.split_once(':')?;
+
+    Some(Socket {
+        protocol,
+        local: parse_address(fields.get(1)?)?,
+        // Unconnected UDP sockets and listening TCP sockets have an all zero peer
+        remote: if remote.ends_with(":0") && !is_connected(state, is_tcp) {
+            String::new()
+        } else {
+            remote
+        },
+        state: if is_tcp {
+            tcp_state_name(state)
+        } else if state == 1 {
+            "ESTABLISHED"
+        } else {
+            ""
+        },
+        tx_queue: u64::from_str_radix(tx_queue, 16).ok()?,
+        rx_queue: u64::from_str_radix(rx_queue, 16).ok()?,
+        inode: fields.get(9)?.parse().ok()?,
+    })
+}
+
+fn is_connected(state: u8, is_tcp: bool) -> bool {
+    if is_tcp {
+        !matches!(state, 0x07 | 0x0A)
+    } else {
+        state == 1
+    }
+}
+
+/// Addresses are the IP as hex 32-bit words in host byte order, and the port in hex.
+fn parse_address(address: &str) -> Option<String> {
+    let (ip, port) = address.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let words: Vec<u32> = (0..ip.len() / 8)
+        .map(|i| u32::from_str_radix(ip.get(i * 8..i * 8 + 8)?, 16).ok())
+        .collect::<Option<_>>()?;
+    let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+
+    match bytes.len() {
+        4 => Some(format!(
+            "{}:{}",
+            Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]),
+            port
+        )),
+        16 => {
+            let bytes: [u8; 16] = bytes.try_into().ok()?;
+            let ip = Ipv6Addr::from(bytes);
+            // IPv4 clients of dual stack sockets show up as ::ffff:a.b.c.d
+            match ip.to_ipv4_mapped() {
+                Some(ipv4) => Some(format!("{}:{}", ipv4, port)),
+                None => Some(format!("[{}]:{}", ip, port)),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn tcp_state_name(state: u8) -> &'static str {
+    match state {
+        0x01 => "ESTABLISHED",
+        0x02 => "SYN_SENT",
+        0x03 => "SYN_RECV",
+        0x04 => "FIN_WAIT1",
+        0x05 => "FIN_WAIT2",
+        0x06 => "TIME_WAIT",
+        0x07 => "CLOSE",
+        0x08 => "CLOSE_WAIT",
+        0x09 => "LAST_ACK",
+        0x0A => "LISTEN",
+        0x0B => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// Set in the flags of listening unix sockets, whose `St` is SS_UNCONNECTED like any other
+// socket that isn't connected
+const SO_ACCEPTCON: u32 = 0x0001_0000;
+
+// Num RefCount Protocol Flags Type St Inode [Path]
+fn parse_unix_line(line: &str) -> Option<Socket> {
+    let fields: Vec<&str> = line.split_whitespace().take(7).collect();
+    // The path is whatever follows the inode, it may contain spaces
+    let mut path = line;
+    for _ in 0..7 {
+        path = path.trim_start();
+        path = &path[path.find(' ').unwrap_or(path.len())..];
+    }
+
+    let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+
+    Some(Socket {
+        protocol: match *fields.get(4)? {
+            "0002" => "unix-dgram",
+            "0005" => "unix-seqpacket",
+            _ => "unix",
+        },
+        local: path.trim().to_string(),
+        remote: String::new(),
+        state: match *fields.get(5)? {
+            _ if flags & SO_ACCEPTCON != 0 => "LISTEN",
+            "01" => "UNCONNECTED",
+            "02" => "CONNECTING",
+            "03" => "CONNECTED",
+            "04" => "DISCONNECTING",
+            _ => "",
+        },
+        tx_queue: 0,
+        rx_queue: 0,
+        inode: fields.get(6)?.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_listening_unix_socket() {
+        let socket = parse_unix_line(
+            "000000002946db23: 00000002 00000000 00010000 0001 01 107993 /tmp/app.sock",
+        )
+        .unwrap();
+        assert_eq!(socket.protocol, "unix");
+        assert_eq!(socket.state, "LISTEN");
+        assert_eq!(socket.local, "/tmp/app.sock");
+        assert_eq!(socket.inode, 107993);
+    }
+
+    #[test]
+    fn parses_unconnected_unix_sockets() {
+        let bound = parse_unix_line(
+            "0000000035ab796e: 00000002 00000000 00000000 0002 01 112172 /tmp/dgram.sock",
+        )
+        .unwrap();
+        assert_eq!(bound.protocol, "unix-dgram");
+        assert_eq!(bound.state, "UNCONNECTED");
+        assert_eq!(bound.local, "/tmp/dgram.sock");
+
+        let unbound =
+            parse_unix_line("00000000080b2783: 00000002 00000000 00000000 0002 01 112173").unwrap();
+        assert_eq!(unbound.state, "UNCONNECTED");
+        assert_eq!(unbound.local, "");
+        assert_eq!(unbound.inode, 112173);
+    }
+
+    #[test]
+    fn parses_connected_unix_socket() {
+        let socket =
+            parse_unix_line("000000009a2926b3: 00000003 00000000 00000000 0001 03   923").unwrap();
+        assert_eq!(socket.state, "CONNECTED");
+        assert_eq!(socket.inode, 923);
+    }
+
+    #[test]
+    fn keeps_spaces_in_unix_socket_paths() {
+        let socket = parse_unix_line(
+            "000000002946db23: 00000002 00000000 00010000 0001 01 107993 /tmp/my app.sock",
+        )
+        .unwrap();
+        assert_eq!(socket.local, "/tmp/my app.sock");
+    }
+}
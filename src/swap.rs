@@ -0,0 +1,198 @@
+use std::{fs, path::Path};
+
+use ratatui::{
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Borders, LineGauge, Paragraph},
+};
+use sysinfo::{System, SystemExt};
+
+use crate::memory::MemInfo;
+
+/// Entry of `/proc/swaps`, sizes in bytes.
+pub struct SwapDevice {
+    pub name: String,
+    pub kind: String,
+    pub size: u64,
+    pub used: u64,
+    pub priority: i32,
+    // Only known for zram devices, read from their mm_stat
+    pub compression_ratio: Option<f64>,
+}
+
+impl SwapDevice {
+    fn is_zram(&self) -> bool {
+        self.name.starts_with("/dev/zram")
+    }
+}
+
+pub struct Zswap {
+    pub enabled: bool,
+    pub compression_ratio: Option<f64>,
+}
+
+pub struct SwapInfo {
+    pub devices: Vec<SwapDevice>,
+    pub zswap: Zswap,
+}
+
+impl SwapInfo {
+    pub fn empty() -> SwapInfo {
+        SwapInfo {
+            devices: Vec::new(),
+            zswap: Zswap {
+                enabled: false,
+                compression_ratio: None,
+            },
+        }
+    }
+
+    pub fn read(meminfo: Option<&MemInfo>) -> SwapInfo {
+        let devices = fs::read_to_string("/proc/swaps")
+            .map(|content| parse_swaps(&content))
+            .unwrap_or_default();
+
+        let enabled = fs::read_to_string("/sys/module/zswap/parameters/enabled")
+            .map(|enabled| matches!(enabled.trim(), "Y" | "1"))
+            .unwrap_or(false);
+        // Zswapped is the uncompressed size of what Zswap holds compressed (Linux 5.19+)
+        let compression_ratio = meminfo
+            .filter(|meminfo| meminfo.zswap > 0)
+            .map(|meminfo| meminfo.zswapped as f64 / meminfo.zswap as f64);
+
+        SwapInfo {
+            devices,
+            zswap: Zswap {
+                enabled,
+                compression_ratio,
+            },
+        }
+    }
+}
+
+fn parse_swaps(content: &str) -> Vec<SwapDevice> {
+    content
+        .lines()
+        .skip(1) // header
+        .filter_map(|line| {
+            // The file name may contain spaces, so take the fixed columns from the end
+            let mut fields = line.split_whitespace().rev();
+            let priority = fields.next()?.parse().ok()?;
+            let used: u64 = fields.next()?.parse().ok()?;
+            let size: u64 = fields.next()?.parse().ok()?;
+            let kind = fields.next()?.to_string();
+            let name = fields
+                .rev()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .replace("\\040", " ");
+
+            let mut device = SwapDevice {
+                name,
+                kind,
+                size: size * 1024,
+                used: used * 1024,
+                priority,
+                compression_ratio: None,
+            };
+            if device.is_zram() {
+                device.compression_ratio = read_zram_compression_ratio(&device.name);
+            }
+            Some(device)
+        })
+        .collect()
+}
+
+fn read_zram_compression_ratio(device: &str) -> Option<f64> {
+    let block = Path::new(device).file_name()?;
+    let mm_stat = fs::read_to_string(Path::new("/sys/block").join(block).join("mm_stat")).ok()?;
+    let mut fields = mm_stat.split_whitespace();
+    let original: u64 = fields.next()?.parse().ok()?;
+    let compressed: u64 = fields.next()?.parse().ok()?;
+
+    if compressed == 0 {
+        return None;
+    }
+    Some(original as f64 / compressed as f64)
+}
+
+pub struct SwapWidget<'a> {
+    pub block: Block<'a>,
+    // None when there's no swap, the devices paragraph then explains why
+    pub gauge: Option<LineGauge<'a>>,
+    pub devices: Paragraph<'a>,
+}
+
+pub fn create_swap_widget<'a>(sys: &System, swap_info: &SwapInfo) -> SwapWidget<'a> {
+    let style = Style::default().fg(Color::LightMagenta);
+    let total_swap = sys.total_swap();
+
+    // Without swap there's nothing to divide by, so show the state instead of an empty gauge
+    if total_swap == 0 {
+        let mut lines = vec![Line::from("No swap configured")];
+        if swap_info.zswap.enabled {
+            lines.push(Line::from(
+                "zswap is enabled but has no swap device to back it",
+            ));
+        }
+
+        return SwapWidget {
+            block: Block::default()
+                .title("Swap Usage, No swap")
+                .borders(Borders::all())
+                .style(Style::default().fg(Color::DarkGray)),
+            gauge: None,
+            devices: Paragraph::new(lines),
+        };
+    }
+
+    let total_swap_mb = total_swap / 1024 / 1024;
+    let used_swap_mb = sys.used_swap() / 1024 / 1024;
+    let swap_ratio = (sys.used_swap() as f64 / total_swap as f64).clamp(0.0, 1.0);
+
+    let mut lines: Vec<Line> = swap_info
+        .devices
+        .iter()
+        .map(|device| {
+            let compression = device
+                .compression_ratio
+                .map_or(String::new(), |ratio| format!(", zram {:.1}x", ratio));
+            Line::from(format!(
+                "{} [{}, prio {}{}]: {}/{} MB",
+                device.name,
+                device.kind,
+                device.priority,
+                compression,
+                device.used / 1024 / 1024,
+                device.size / 1024 / 1024
+            ))
+        })
+        .collect();
+
+    if swap_info.zswap.enabled {
+        let compression = swap_info
+            .zswap
+            .compression_ratio
+            .map_or(String::new(), |ratio| {
+                format!(", {:.1}x compression", ratio)
+            });
+        lines.push(Line::from(format!("zswap enabled{}", compression)));
+    }
+
+    SwapWidget {
+        block: Block::default()
+            .title(format!(
+                "Swap Usage, Total: {} MB, Used: {} MB",
+                total_swap_mb, used_swap_mb
+            ))
+            .borders(Borders::all())
+            .style(style),
+        gauge: Some(
+            LineGauge::default()
+                .gauge_style(style)
+                .ratio(swap_ratio)
+                .label(format!("{:>3}%", (swap_ratio * 100.0).round())),
+        ),
+        devices: Paragraph::new(lines),
+    }
+}
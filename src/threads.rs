@@ -0,0 +1,220 @@
+use std::{collections::HashMap, fs, time::Instant};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Constraint,
+    style::{Color, Style},
+    widgets::{Block, Borders, Row, Table},
+};
+use sysinfo::{Pid, ProcessStatus};
+
+use crate::{
+    details::format_duration,
+    layout::table_window,
+    procstat::{clock_ticks_per_second, parse_stat},
+    query::state_name,
+};
+
+const THREAD_COLUMN_WIDTHS: [Constraint; 6] = [
+    Constraint::Length(9),
+    Constraint::Length(16),
+    Constraint::Length(11),
+    Constraint::Length(7),
+    Constraint::Length(8),
+    Constraint::Length(12),
+];
+
+/// One entry of `/proc/<pid>/task`.
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+    pub state: ProcessStatus,
+    // None until there are two samples to compute it from
+    pub cpu_usage: Option<f64>,
+    pub last_cpu: u32,
+    // Clock ticks spent in user and kernel mode
+    pub cpu_ticks: u64,
+}
+
+/// Threads of one process, refreshed with the rest of the data while open. CPU usage is
+/// computed from the CPU time each thread gained since the previous refresh.
+pub struct ThreadsView {
+    pid: Pid,
+    name: String,
+    scroll: usize,
+    threads: Vec<ThreadInfo>,
+    // Whether the task directory could be read, it can't once the process is gone
+    exited: bool,
+    last_refresh: Option<Instant>,
+}
+
+pub enum ThreadsViewAction {
+    None,
+    Close,
+}
+
+impl ThreadsView {
+    pub fn new(pid: Pid, name: String) -> Self {
+        let mut view = Self {
+            pid,
+            name,
+            scroll: 0,
+            threads: Vec::new(),
+            exited: false,
+            last_refresh: None,
+        };
+        view.refresh();
+        view
+    }
+
+    pub fn refresh(&mut self) {
+        let now = Instant::now();
+        let seconds = self
+            .last_refresh
+            .map(|last_refresh| now.duration_since(last_refresh).as_secs_f64())
+            .filter(|seconds| *seconds > 0.0);
+        self.last_refresh = Some(now);
+
+        let previous: HashMap<u32, u64> = self
+            .threads
+            .iter()
+            .map(|thread| (thread.tid, thread.cpu_ticks))
+            .collect();
+
+        let Some(mut threads) = read_threads(self.pid) else {
+            self.threads.clear();
+            self.exited = true;
+            return;
+        };
+
+        for thread in &mut threads {
+            // Threads that appeared since the previous refresh have nothing to compare with
+            thread.cpu_usage = seconds
+                .zip(previous.get(&thread.tid))
+                .map(|(seconds, ticks)| {
+                    thread.cpu_ticks.saturating_sub(*ticks) as f64
+                        / clock_ticks_per_second()
+                        / seconds
+                        * 100.0
+                });
+        }
+
+        // The busiest threads first, so a spinning one is at the top
+        threads.sort_by(|a, b| {
+            b.cpu_usage
+                .unwrap_or(0.0)
+                .total_cmp(&a.cpu_usage.unwrap_or(0.0))
+                .then(a.tid.cmp(&b.tid))
+        });
+        self.threads = threads;
+    }
+
+    pub fn handle_key(&mut self, code: KeyCode) -> ThreadsViewAction {
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('H') => {
+                return ThreadsViewAction::Close
+            }
+            _ => {}
+        }
+        ThreadsViewAction::None
+    }
+}
+
+fn read_threads(pid: Pid) -> Option<Vec<ThreadInfo>> {
+    let entries = fs::read_dir(format!("/proc/{}/task", pid)).ok()?;
+
+    // Threads can exit between listing the directory and reading their files, they're skipped
+    let threads = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let tid: u32 = entry.file_name().to_str()?.parse().ok()?;
+            let stat = parse_stat(&fs::read_to_string(entry.path().join("stat")).ok()?)?;
+            let name = fs::read_to_string(entry.path().join("comm")).ok()?;
+
+            Some(ThreadInfo {
+                tid,
+                name: name.trim_end().to_string(),
+                state: thread_status(stat.state),
+                cpu_usage: None,
+                last_cpu: stat.last_cpu,
+                cpu_ticks: stat.utime + stat.stime,
+            })
+        })
+        .collect();
+    Some(threads)
+}
+
+/// Status of a state letter from `/proc/<pid>/task/<tid>/stat`, read like sysinfo reads the
+/// ones of processes.
+fn thread_status(state: char) -> ProcessStatus {
+    #[cfg(target_os = "linux")]
+    return ProcessStatus::from(state);
+    // Threads are only listed from /proc, other systems never get here
+    #[cfg(not(target_os = "linux"))]
+    return ProcessStatus::Unknown(state as u32);
+}
+
+pub struct ThreadsTable<'a> {
+    pub chart: Table<'a>,
+    pub max_scroll: usize,
+    pub scroll_position: usize,
+}
+
+pub fn create_threads_table<'a>(view: &mut ThreadsView, height: usize) -> ThreadsTable<'a> {
+    let window = table_window(view.threads.len(), height, view.scroll, None);
+    view.scroll = window.scroll;
+
+    let header = Row::new(vec!["TID", "Name", "State", "CPU%", "Last CPU", "CPU time"])
+        .style(Style::default().fg(Color::Gray));
+    let rows: Vec<Row> = view
+        .threads
+        .iter()
+        .skip(view.scroll)
+        .take(window.visible_lines)
+        .map(|thread| {
+            Row::new(vec![
+                thread.tid.to_string(),
+                thread.name.clone(),
+                state_name(thread.state).to_string(),
+                thread
+                    .cpu_usage
+                    .map_or("-".to_string(), |usage| format!("{:.1}", usage)),
+                thread.last_cpu.to_string(),
+                format_duration(thread.cpu_ticks as f64 / clock_ticks_per_second()),
+            ])
+        })
+        .collect();
+
+    let title = if view.exited {
+        format!(
+            "Threads of {} ({}) | Process has exited",
+            view.name, view.pid
+        )
+    } else {
+        format!(
+            "Threads of {} ({}): {} | j/k: Scroll | Esc: Close",
+            view.name,
+            view.pid,
+            view.threads.len()
+        )
+    };
+
+    ThreadsTable {
+        chart: Table::new(rows)
+            .header(header)
+            .widths(&THREAD_COLUMN_WIDTHS)
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::all())
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .column_spacing(1),
+        max_scroll: window.max_scroll,
+        scroll_position: view.scroll,
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fs, time::Instant};
+
+/// Cumulative counters from `/proc/vmstat`.
+#[derive(Clone, Copy, Default)]
+struct VmStatCounters {
+    page_in: u64,
+    page_out: u64,
+    swap_in: u64,
+    swap_out: u64,
+    faults: u64,
+    major_faults: u64,
+    oom_kills: u64,
+}
+
+impl VmStatCounters {
+    fn read() -> Option<VmStatCounters> {
+        let content = fs::read_to_string("/proc/vmstat").ok()?;
+
+        let mut counters = VmStatCounters::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let value = value.trim().parse().unwrap_or(0);
+            match key {
+                "pgpgin" => counters.page_in = value,
+                "pgpgout" => counters.page_out = value,
+                "pswpin" => counters.swap_in = value,
+                "pswpout" => counters.swap_out = value,
+                "pgfault" => counters.faults = value,
+                "pgmajfault" => counters.major_faults = value,
+                "oom_kill" => counters.oom_kills = value,
+                _ => {}
+            }
+        }
+        Some(counters)
+    }
+}
+
+/// Per second rates between the last two `/proc/vmstat` samples.
+#[derive(Clone, Copy, Default)]
+pub struct VmStatRates {
+    pub page_in_kb: f64,
+    pub page_out_kb: f64,
+    pub swap_in_pages: f64,
+    pub swap_out_pages: f64,
+    pub major_faults: f64,
+    pub minor_faults: f64,
+    pub oom_kills: f64,
+    pub total_oom_kills: u64,
+}
+
+impl VmStatRates {
+    /// Named values, in the order they are displayed.
+    pub fn metrics(&self) -> [(&'static str, f64, &'static str); 7] {
+        [
+            ("Page in", self.page_in_kb, "KB/s"),
+            ("Page out", self.page_out_kb, "KB/s"),
+            ("Swap in", self.swap_in_pages, "pages/s"),
+            ("Swap out", self.swap_out_pages, "pages/s"),
+            ("Major faults", self.major_faults, "/s"),
+            ("Minor faults", self.minor_faults, "/s"),
+            ("OOM kills", self.oom_kills, "/s"),
+        ]
+    }
+
+    /// Values by the names used in exports, rates are per second.
+    pub fn fields(&self) -> [(&'static str, f64); 8] {
+        [
+            ("page_in_kb", self.page_in_kb),
+            ("page_out_kb", self.page_out_kb),
+            ("swap_in_pages", self.swap_in_pages),
+            ("swap_out_pages", self.swap_out_pages),
+            ("major_faults", self.major_faults),
+            ("minor_faults", self.minor_faults),
+            ("oom_kills", self.oom_kills),
+            ("total_oom_kills", self.total_oom_kills as f64),
+        ]
+    }
+}
+
+pub struct VmStatSampler {
+    previous: Option<(Instant, VmStatCounters)>,
+    pub rates: Option<VmStatRates>,
+}
+
+impl VmStatSampler {
+    pub fn new() -> Self {
+        Self {
+            previous: None,
+            rates: None,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let Some(counters) = VmStatCounters::read() else {
+            self.rates = None;
+            return;
+        };
+        let now = Instant::now();
+
+        if let Some((previous_time, previous)) = self.previous {
+            let seconds = now.duration_since(previous_time).as_secs_f64();
+            if seconds > 0.0 {
+                let rate =
+                    |current: u64, previous: u64| current.saturating_sub(previous) as f64 / seconds;
+                // pgfault counts every fault, the minor ones are what's left after the major ones
+                let minor_faults = counters.faults.saturating_sub(counters.major_faults);
+                let previous_minor_faults = previous.faults.saturating_sub(previous.major_faults);
+
+                self.rates = Some(VmStatRates {
+                    page_in_kb: rate(counters.page_in, previous.page_in),
+                    page_out_kb: rate(counters.page_out, previous.page_out),
+                    swap_in_pages: rate(counters.swap_in, previous.swap_in),
+                    swap_out_pages: rate(counters.swap_out, previous.swap_out),
+                    major_faults: rate(counters.major_faults, previous.major_faults),
+                    minor_faults: rate(minor_faults, previous_minor_faults),
+                    oom_kills: rate(counters.oom_kills, previous.oom_kills),
+                    total_oom_kills: counters.oom_kills,
+                });
+            }
+        }
+
+        self.previous = Some((now, counters));
+    }
+}